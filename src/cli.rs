@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::{Parser, ValueHint};
 use pling::clap::Args as Pling;
 use regex::Regex;
@@ -51,6 +53,19 @@ pub enum Cli {
         #[command(flatten)]
         notifications: Pling,
 
+        /// Path of the configuration file in the YAML, TOML or JSON format detected by its extension.
+        ///
+        /// Defaults to `website-stalker.yaml`, `website-stalker.toml` or `website-stalker.json` in the working directory.
+        ///
+        /// Sites of the files in the include directory next to it are added.
+        /// For `website-stalker.yaml` these are `website-stalker.d/*.yaml` (or `.toml` and `.json`).
+        #[arg(
+            long,
+            env = "WEBSITE_STALKER_CONFIG",
+            value_hint = ValueHint::FilePath,
+        )]
+        config: Option<PathBuf>,
+
         /// Used as the From header in the web requests.
         ///
         /// See <https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/From>
@@ -67,11 +82,19 @@ pub enum Cli {
         )]
         from: Option<String>,
 
+        /// Only run sites having at least one of these tags
+        #[arg(long, value_hint = ValueHint::Other, conflicts_with = "all")]
+        tag: Vec<String>,
+
+        /// Do not run sites having any of these tags
+        #[arg(long, value_hint = ValueHint::Other, conflicts_with = "all")]
+        exclude_tag: Vec<String>,
+
         /// Filter the sites to be run (case insensitive regular expression)
         #[arg(
             value_hint = ValueHint::Other,
             conflicts_with = "all",
-            required_unless_present = "all",
+            required_unless_present_any = ["all", "tag", "exclude_tag"],
         )]
         site_filter: Option<Regex>,
     },
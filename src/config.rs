@@ -1,11 +1,21 @@
+use core::num::NonZeroUsize;
+use core::time::Duration;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
 use anyhow::Context as _;
 use schemars::JsonSchema;
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use url::Url;
 
-use crate::http::validate_from;
+use crate::editor::{Editor, chain};
+use crate::host::{Delay, Host};
+use crate::http::{Body, validate_from};
 use crate::logger;
-use crate::site::{Options, Site};
+use crate::retry::Retry;
+use crate::secrets;
+use crate::site::{Options, Site, SiteDefaults};
 
 /// # Website Stalker configuration file
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -16,9 +26,67 @@ pub struct Config {
     #[schemars(email)]
     pub from: String,
 
+    /// Retry failed requests of all sites unless the site specifies its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<Retry>,
+
+    /// Wait time between requests to the same host. Defaults to 5 seconds.
+    #[serde(default, with = "humantime_serde")]
+    #[schemars(with = "Option<String>")]
+    pub host_delay: Option<Duration>,
+
+    /// Random additional wait time between requests to the same host up to this value
+    #[serde(default, with = "humantime_serde")]
+    #[schemars(with = "Option<String>")]
+    pub host_jitter: Option<Duration>,
+
+    /// Maximum amount of requests running at the same time. Unlimited when not specified.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_concurrent_requests: Option<NonZeroUsize>,
+
+    /// Check the robots.txt of the hosts and skip the sites it disallows
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub respect_robots_txt: bool,
+
+    /// Options for all sites unless they specify their own
+    #[serde(default)]
+    pub defaults: Defaults,
+
+    /// Options for specific hosts like `edjopato.de`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub hosts: BTreeMap<String, Host>,
+
+    /// Named lists of editors which can be used by sites via `chain: name`
+    #[serde(default)]
+    pub editor_chains: EditorChains,
+
+    /// Can be empty when the include directory like `website-stalker.d/` contains the sites
+    #[serde(default)]
     pub sites: Vec<SiteEntry>,
 }
 
+/// Files in the include directory like `website-stalker.d/*.yaml` only contain sites
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Include {
+    sites: Vec<SiteEntry>,
+}
+
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Defaults {
+    #[serde(flatten)]
+    pub site: SiteDefaults,
+}
+
+// serde_yaml only parses editors like `css_remove: nav` without a YAML tag like `!css_remove`
+// within a flattened struct. A plain map field fails with "expected a YAML tag".
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+pub struct EditorChains {
+    #[serde(flatten)]
+    pub chains: BTreeMap<String, Vec<Editor>>,
+}
+
 /// Single or multiple URLs
 #[derive(Debug, Deserialize, JsonSchema)]
 #[serde(untagged)]
@@ -34,6 +102,13 @@ impl UrlVariants {
             Self::Many(many) => many.is_empty(),
         }
     }
+
+    fn first(&self) -> Option<&Url> {
+        match self {
+            Self::Single(url) => Some(url),
+            Self::Many(many) => many.first(),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -42,44 +117,179 @@ pub struct SiteEntry {
     pub url: UrlVariants,
     #[serde(flatten)]
     pub options: Options,
+    #[serde(skip)]
+    #[schemars(skip)]
+    uninterpolated: Option<Uninterpolated>,
+}
+
+impl SiteEntry {
+    /// First url of the entry without secrets for error messages
+    fn display_url(&self) -> Option<&Url> {
+        self.uninterpolated
+            .as_ref()
+            .map_or(&self.url, |uninterpolated| &uninterpolated.url)
+            .first()
+    }
+}
+
+/// `url` and `body` of a site with the `${…}` references replaced by their names.
+/// The filenames are based on them so they neither contain secrets nor change with them.
+#[derive(Debug, Deserialize)]
+struct Uninterpolated {
+    url: UrlVariants,
+    #[serde(default)]
+    body: Option<Body>,
+}
+
+impl Uninterpolated {
+    fn file_path(&self, index: usize, options: &Options) -> Option<PathBuf> {
+        let url = match &self.url {
+            UrlVariants::Single(url) => (index == 0).then_some(url),
+            UrlVariants::Many(many) => many.get(index),
+        }?;
+        let mut options = options.clone();
+        options.body.clone_from(&self.body);
+        let site = Site {
+            url: url.clone(),
+            options,
+        };
+        Some(site.to_file_path())
+    }
+}
+
+/// Parsed files containing sites
+trait WithSites {
+    fn sites_mut(&mut self) -> &mut [SiteEntry];
+}
+
+impl WithSites for Config {
+    fn sites_mut(&mut self) -> &mut [SiteEntry] {
+        &mut self.sites
+    }
+}
+
+impl WithSites for Include {
+    fn sites_mut(&mut self) -> &mut [SiteEntry] {
+        &mut self.sites
+    }
 }
 
 impl Config {
     pub const EXAMPLE: &str = include_str!("../sites/website-stalker.yaml");
 
-    pub fn load(cli_from: Option<String>) -> anyhow::Result<Self> {
-        let filecontent = std::fs::read_to_string("website-stalker.yaml")?;
-        let mut config = serde_yaml::from_str::<Self>(&filecontent)?;
+    const DEFAULT_PATHS: [&str; 3] = [
+        "website-stalker.yaml",
+        "website-stalker.toml",
+        "website-stalker.json",
+    ];
+
+    /// The first existing of `website-stalker.yaml`, `.toml` or `.json` in the working directory
+    pub fn default_path() -> PathBuf {
+        let path = Self::DEFAULT_PATHS
+            .into_iter()
+            .find(|path| Path::new(path).is_file())
+            .unwrap_or(Self::DEFAULT_PATHS[0]);
+        PathBuf::from(path)
+    }
+
+    pub fn load(path: &Path, cli_from: Option<String>) -> anyhow::Result<Self> {
+        let format = Format::from_path(path)?;
+        let filecontent = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        let mut config = format.parse::<Self>(&filecontent)?;
+
+        for include in include_files(path)? {
+            let format = Format::from_path(&include)?;
+            let filecontent = std::fs::read_to_string(&include)
+                .with_context(|| format!("failed to read {}", include.display()))?;
+            let include_config = format
+                .parse::<Include>(&filecontent)
+                .with_context(|| format!("failed to parse {}", include.display()))?;
+            config.sites.extend(include_config.sites);
+        }
 
         if let Some(from) = cli_from {
             config.from = from;
         }
 
+        config.resolve_editor_chains()?;
         config.validate()?;
         Ok(config)
     }
 
+    /// Replace the chain references of the sites with the editors of the chains
+    fn resolve_editor_chains(&mut self) -> anyhow::Result<()> {
+        let chains = &self.editor_chains.chains;
+        for (name, editors) in chains {
+            chain::resolve(editors, chains)
+                .with_context(|| format!("editor_chains {name} is invalid"))?;
+        }
+        for (index, entry) in self.sites.iter_mut().enumerate() {
+            entry.options.editors =
+                chain::resolve(&entry.options.editors, chains).with_context(|| {
+                    let url = entry.display_url().map_or("", Url::as_str);
+                    format!("site[{index}] {url}")
+                })?;
+        }
+        self.defaults.site.editors = chain::resolve(&self.defaults.site.editors, chains)?;
+        for host in self.hosts.values_mut() {
+            host.site.editors = chain::resolve(&host.site.editors, chains)?;
+        }
+        Ok(())
+    }
+
+    /// Sites with their options merged in the order site, `hosts` entry and `defaults`
     pub fn get_sites(&self) -> Vec<Site> {
         let mut result = Vec::new();
         for entry in &self.sites {
-            match &entry.url {
-                UrlVariants::Single(url) => result.push(Site {
-                    url: url.clone(),
-                    options: entry.options.clone(),
-                }),
-                UrlVariants::Many(many) => {
-                    for url in many {
-                        result.push(Site {
-                            url: url.clone(),
-                            options: entry.options.clone(),
-                        });
+            let urls = match &entry.url {
+                UrlVariants::Single(url) => core::slice::from_ref(url),
+                UrlVariants::Many(many) => many.as_slice(),
+            };
+            for (index, url) in urls.iter().enumerate() {
+                let mut options = entry.options.clone();
+                options.retry = options.retry.or(self.retry);
+                if !options.ignore_defaults {
+                    if let Some(host) = url.host_str().and_then(|host| self.hosts.get(host)) {
+                        host.site.apply_to(&mut options);
                     }
+                    self.defaults.site.apply_to(&mut options);
+                }
+                if options.filename.is_none() {
+                    options.filename = entry
+                        .uninterpolated
+                        .as_ref()
+                        .and_then(|uninterpolated| uninterpolated.file_path(index, &options));
                 }
+                result.push(Site {
+                    url: url.clone(),
+                    options,
+                });
             }
         }
         result
     }
 
+    /// Wait time between requests to the same host when the host has nothing specific configured
+    pub fn default_host_delay(&self) -> Delay {
+        Delay {
+            delay: self.host_delay.unwrap_or(Delay::DEFAULT.delay),
+            jitter: self.host_jitter.unwrap_or(Delay::DEFAULT.jitter),
+        }
+    }
+
+    /// Wait time between requests to the given host
+    pub fn host_delay(&self, host: &str) -> Delay {
+        let default = self.default_host_delay();
+        let Some(specific) = self.hosts.get(host) else {
+            return default;
+        };
+        Delay {
+            delay: specific.delay.unwrap_or(default.delay),
+            jitter: specific.jitter.unwrap_or(default.jitter),
+        }
+    }
+
     fn validate(&self) -> anyhow::Result<()> {
         const OLD_PLING_ENV_VARS: [&str; 20] = [
             "EMAIL_FROM",
@@ -128,21 +338,666 @@ impl Config {
 
         let sites = self.get_sites();
         Site::validate_no_duplicate(&sites)?;
+
+        for host in self.hosts.keys() {
+            anyhow::ensure!(
+                sites.iter().any(|site| site.url.host_str() == Some(host)),
+                "hosts entry {host} matches no site"
+            );
+        }
         Ok(())
     }
 }
 
+/// File format of the config detected by its extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Toml,
+    Yaml,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        let extension = path.extension().and_then(|ext| ext.to_str());
+        match extension {
+            Some("json") => Ok(Self::Json),
+            Some("toml") => Ok(Self::Toml),
+            Some("yaml" | "yml") => Ok(Self::Yaml),
+            _ => anyhow::bail!(
+                "unsupported config file format of {}. Use .yaml, .toml or .json",
+                path.display()
+            ),
+        }
+    }
+
+    /// Parse the text and interpolate references like `${ENV_VAR}` in the options supporting them
+    fn parse<T: DeserializeOwned + WithSites>(self, text: &str) -> anyhow::Result<T> {
+        if !text.contains("${") {
+            // Directly parsing keeps the location of errors
+            return Ok(match self {
+                Self::Json => serde_json::from_str(text)?,
+                Self::Toml => toml::from_str(text)?,
+                Self::Yaml => serde_yaml::from_str(text)?,
+            });
+        }
+        let mut value = match self {
+            Self::Json => serde_json::from_str::<serde_yaml::Value>(text)?,
+            Self::Toml => toml::from_str::<serde_yaml::Value>(text)?,
+            Self::Yaml => serde_yaml::from_str::<serde_yaml::Value>(text)?,
+        };
+        let uninterpolated = interpolate(&mut value)?;
+        let mut parsed = serde_yaml::from_value::<T>(value)?;
+        for (entry, uninterpolated) in parsed.sites_mut().iter_mut().zip(uninterpolated) {
+            entry.uninterpolated = uninterpolated;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Options of sites which support `${…}` references
+const INTERPOLATED_SITE_OPTIONS: [&str; 4] = ["url", "headers", "body", "session"];
+
+/// Interpolate `from`, the headers of `defaults` and `hosts` entries and the [`INTERPOLATED_SITE_OPTIONS`].
+/// Other strings like the ones of editors are kept as `${1}` is common there.
+///
+/// Returns the uninterpolated values of each site.
+fn interpolate(value: &mut serde_yaml::Value) -> anyhow::Result<Vec<Option<Uninterpolated>>> {
+    let Some(config) = value.as_mapping_mut() else {
+        return Ok(Vec::new());
+    };
+    if let Some(from) = config.get_mut("from") {
+        secrets::interpolate_value(from)?;
+    }
+    if let Some(headers) = config
+        .get_mut("defaults")
+        .and_then(|defaults| defaults.get_mut("headers"))
+    {
+        secrets::interpolate_value(headers)?;
+    }
+    if let Some(hosts) = config
+        .get_mut("hosts")
+        .and_then(serde_yaml::Value::as_mapping_mut)
+    {
+        for headers in hosts
+            .values_mut()
+            .filter_map(|host| host.get_mut("headers"))
+        {
+            secrets::interpolate_value(headers)?;
+        }
+    }
+    let Some(sites) = config
+        .get_mut("sites")
+        .and_then(serde_yaml::Value::as_sequence_mut)
+    else {
+        return Ok(Vec::new());
+    };
+    let mut result = Vec::with_capacity(sites.len());
+    for (index, site) in sites.iter_mut().enumerate() {
+        let Some(site) = site.as_mapping_mut() else {
+            result.push(None);
+            continue;
+        };
+        let uninterpolated = uninterpolated(site).with_context(|| {
+            format!("site[{index}] needs a filename as its url or body is invalid without the ${{…}} references")
+        })?;
+        result.push(uninterpolated);
+        for key in INTERPOLATED_SITE_OPTIONS {
+            if let Some(value) = site.get_mut(key) {
+                secrets::interpolate_value(value)?;
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// The `url` and `body` with references replaced by their names.
+/// Only needed when they contain references and the filename is not specified.
+fn uninterpolated(site: &serde_yaml::Mapping) -> anyhow::Result<Option<Uninterpolated>> {
+    if site.contains_key("filename") || !site.contains_key("url") {
+        return Ok(None);
+    }
+    let mut original = serde_yaml::Mapping::new();
+    for key in ["url", "body"] {
+        if let Some(value) = site.get(key) {
+            original.insert(key.into(), value.clone());
+        }
+    }
+    let original = serde_yaml::Value::Mapping(original);
+    let mut placeholders = original.clone();
+    secrets::placeholder_value(&mut placeholders)?;
+    if placeholders == original {
+        return Ok(None);
+    }
+    // serde_yaml::from_value expects YAML tags for enums like the body
+    let placeholders = serde_json::to_value(placeholders)?;
+    Ok(Some(serde_json::from_value(placeholders)?))
+}
+
+/// The include directory next to the config like `website-stalker.d` for `website-stalker.yaml`
+pub fn include_dir(path: &Path) -> Option<PathBuf> {
+    let stem = path.file_stem()?;
+    Some(path.with_file_name(format!("{}.d", stem.to_string_lossy())))
+}
+
+/// Files of the include directory next to the config sorted by their name.
+/// For `website-stalker.yaml` these are `website-stalker.d/*.yaml` (or `.toml` and `.json`).
+fn include_files(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let Some(dir) = include_dir(path) else {
+        return Ok(Vec::new());
+    };
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut files = std::fs::read_dir(&dir)
+        .with_context(|| format!("failed to read the include directory {}", dir.display()))?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    files.retain(|file| file.is_file() && Format::from_path(file).is_ok());
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+fn load_test_dir(files: &[(&str, &str)]) -> anyhow::Result<Config> {
+    let tempdir = tempfile::Builder::new()
+        .prefix("website-stalker-testing-")
+        .tempdir()?;
+    for (name, content) in files {
+        let path = tempdir.path().join(name);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(path, content)?;
+    }
+    // The first file is the main config
+    Config::load(&tempdir.path().join(files[0].0), None)
+}
+
+#[test]
+fn includes_are_merged() {
+    let config = load_test_dir(&[
+        (
+            "stalk.yaml",
+            "from: dummy@example.com\nsites:\n  - url: https://edjopato.de/\n",
+        ),
+        ("stalk.d/b.yaml", "sites:\n  - url: https://edjopato.de/b\n"),
+        ("stalk.d/a.yaml", "sites:\n  - url: https://edjopato.de/a\n"),
+        ("stalk.d/notes.txt", "ignored"),
+    ])
+    .unwrap();
+    let urls = config
+        .get_sites()
+        .iter()
+        .map(|site| site.url.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        urls,
+        [
+            "https://edjopato.de/",
+            "https://edjopato.de/a",
+            "https://edjopato.de/b"
+        ]
+    );
+}
+
+#[test]
+fn toml_config() {
+    let config = load_test_dir(&[
+        (
+            "stalk.toml",
+            r#"
+from = "dummy@example.com"
+host_delay = "2s"
+
+[[sites]]
+url = "https://edjopato.de/"
+headers = ["Accept-Language: de"]
+editors = [{ css_remove = "nav" }, "html_prettify", { regex_replace = { pattern = "\\d+", replace = "1" } }]
+
+[[sites]]
+url = ["https://edjopato.de/a", "https://edjopato.de/b"]
+"#,
+        ),
+        (
+            "stalk.d/more.json",
+            r#"{"sites": [{"url": "https://edjopato.de/c", "editors": [{"css_select": "main"}]}]}"#,
+        ),
+    ])
+    .unwrap();
+    assert_eq!(config.host_delay, Some(Duration::from_secs(2)));
+    let sites = config.get_sites();
+    assert_eq!(sites.len(), 4);
+    assert_eq!(sites[0].options.editors.len(), 3);
+    assert_eq!(sites[3].options.editors[0].log_name(), "css_select");
+}
+
+#[test]
+fn json_config() {
+    let config = load_test_dir(&[(
+        "stalk.json",
+        r#"{
+            "from": "dummy@example.com",
+            "editor_chains": {"pretty": ["html_prettify"]},
+            "sites": [{"url": "https://edjopato.de/", "editors": [{"chain": "pretty"}, {"css_remove": "nav"}]}]
+        }"#,
+    )])
+    .unwrap();
+    let names = config.get_sites()[0]
+        .options
+        .editors
+        .iter()
+        .map(Editor::log_name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["html_prettify", "css_remove"]);
+}
+
+#[test]
+#[should_panic = "unknown field `ignore_eror`"]
+fn defaults_deny_unknown_fields() {
+    serde_yaml::from_str::<Config>(
+        "
+defaults:
+  ignore_eror: true
+sites:
+  - url: https://edjopato.de/
+",
+    )
+    .unwrap();
+}
+
+#[test]
+#[should_panic = "site list is empty"]
+fn toml_config_is_validated() {
+    load_test_dir(&[("stalk.toml", "from = \"dummy@example.com\"\n")]).unwrap();
+}
+
+#[test]
+#[should_panic = "unsupported config file format"]
+fn unsupported_config_format() {
+    load_test_dir(&[("stalk.ini", "")]).unwrap();
+}
+
+#[test]
+fn only_includes_have_sites() {
+    let config = load_test_dir(&[
+        ("stalk.yaml", "from: dummy@example.com\n"),
+        ("stalk.d/a.yaml", "sites:\n  - url: https://edjopato.de/\n"),
+    ])
+    .unwrap();
+    assert_eq!(config.get_sites().len(), 1);
+}
+
+#[test]
+#[should_panic = "duplicates"]
+fn include_duplicates_are_detected() {
+    load_test_dir(&[
+        (
+            "stalk.yaml",
+            "from: dummy@example.com\nsites:\n  - url: https://edjopato.de/\n",
+        ),
+        ("stalk.d/a.yaml", "sites:\n  - url: https://edjopato.de/\n"),
+    ])
+    .unwrap();
+}
+
+#[test]
+#[should_panic = "a.yaml"]
+fn include_only_contains_sites() {
+    load_test_dir(&[
+        (
+            "stalk.yaml",
+            "from: dummy@example.com\nsites:\n  - url: https://edjopato.de/\n",
+        ),
+        ("stalk.d/a.yaml", "from: other@example.com\n"),
+    ])
+    .unwrap();
+}
+
+#[test]
+fn interpolates_strings() {
+    // A secret file instead of an environment variable as the value is redacted in all tests afterwards
+    let dir = tempfile::tempdir().unwrap();
+    let secret = dir.path().join("token");
+    std::fs::write(&secret, "interpolates-strings-token\n").unwrap();
+    let config = Format::Yaml
+        .parse::<Config>(&format!(
+            "
+from: ${{file:{secret}}}@example.com
+sites:
+  - url: https://edjopato.de/?token=${{file:{secret}}}
+    headers:
+      - 'Authorization: Bearer ${{file:{secret}}}'
+    editors:
+      - css_remove: nav
+",
+            secret = secret.display()
+        ))
+        .unwrap();
+    assert_eq!(config.from, "interpolates-strings-token@example.com");
+    let site = &config.get_sites()[0];
+    assert_eq!(
+        site.url.as_str(),
+        "https://edjopato.de/?token=interpolates-strings-token"
+    );
+    assert_eq!(
+        site.options.headers.get("authorization").unwrap(),
+        "Bearer interpolates-strings-token"
+    );
+    assert_eq!(site.options.editors.len(), 1);
+    let path = site.to_file_path();
+    assert!(path.starts_with("de/edjopato"), "{path:?}");
+    assert!(
+        !path
+            .to_string_lossy()
+            .contains("interpolates-strings-token"),
+        "{path:?}"
+    );
+}
+
+#[test]
+fn filename_is_based_on_the_references() {
+    let dir = tempfile::tempdir().unwrap();
+    let secret = dir.path().join("team");
+    let path = |team: &str| {
+        std::fs::write(&secret, team).unwrap();
+        let config = Format::Yaml
+            .parse::<Config>(&format!(
+                "
+sites:
+  - url: https://edjopato.de/?team=${{file:{secret}}}
+    method: POST
+    body:
+      raw: team=${{file:{secret}}}
+",
+                secret = secret.display()
+            ))
+            .unwrap();
+        config.get_sites()[0].to_file_path()
+    };
+    let first = path("filename-team-first");
+    assert_eq!(first, path("filename-team-second"));
+    assert!(
+        !first.to_string_lossy().contains("filename-team"),
+        "{first:?}"
+    );
+    assert!(first.starts_with("de/edjopato"), "{first:?}");
+}
+
+#[test]
+fn editors_are_not_interpolated() {
+    let config = Format::Yaml
+        .parse::<Config>(
+            r#"
+sites:
+  - url: https://edjopato.de/
+    editors:
+      - regex_replace:
+          pattern: (\w+)
+          replace: ${1}x
+      - script:
+          code: "`${url}`"
+"#,
+        )
+        .unwrap();
+    let site = &config.get_sites()[0];
+    let Editor::RegexReplace(regex_replace) = &site.options.editors[0] else {
+        panic!("should be regex_replace");
+    };
+    assert_eq!(regex_replace.replace_all("ab cd"), "abx cdx");
+}
+
 #[test]
 fn example_sites_are_valid() {
     let config = serde_yaml::from_str::<Config>(Config::EXAMPLE).unwrap();
     config.validate_sites().unwrap();
 }
 
+#[test]
+fn site_with_method_and_body() {
+    let config = serde_yaml::from_str::<Config>(
+        "
+sites:
+  - url: https://edjopato.de/search
+    method: POST
+    body:
+      form:
+        q: website stalker
+",
+    )
+    .unwrap();
+    let options = &config.sites[0].options;
+    assert_eq!(options.method, crate::http::Method::Post);
+    assert_eq!(
+        options.body.as_ref().map(Body::to_text).as_deref(),
+        Some("q=website+stalker")
+    );
+}
+
+#[test]
+fn site_retry_overrides_global() {
+    let config = serde_yaml::from_str::<Config>(
+        "
+retry:
+  attempts: 2
+sites:
+  - url: https://edjopato.de/
+  - url: https://edjopato.de/post/
+    retry:
+      attempts: 5
+",
+    )
+    .unwrap();
+    let sites = config.get_sites();
+    assert_eq!(sites[0].options.retry.map(|retry| retry.attempts), Some(2));
+    assert_eq!(sites[1].options.retry.map(|retry| retry.attempts), Some(5));
+}
+
+#[test]
+fn host_delay_specific_overrides_global() {
+    let config = serde_yaml::from_str::<Config>(
+        "
+host_delay: 2s
+host_jitter: 1s
+hosts:
+  edjopato.de:
+    delay: 0s
+sites:
+  - url: https://edjopato.de/
+  - url: https://foo.bar/
+",
+    )
+    .unwrap();
+    config.validate_sites().unwrap();
+    assert_eq!(
+        config.host_delay("edjopato.de"),
+        Delay {
+            delay: Duration::ZERO,
+            jitter: Duration::from_secs(1),
+        }
+    );
+    assert_eq!(
+        config.host_delay("foo.bar"),
+        Delay {
+            delay: Duration::from_secs(2),
+            jitter: Duration::from_secs(1),
+        }
+    );
+}
+
+#[test]
+#[should_panic = "hosts entry foo.bar matches no site"]
+fn validate_fails_on_unused_host() {
+    let config = serde_yaml::from_str::<Config>(
+        "
+hosts:
+  foo.bar:
+    delay: 0s
+sites:
+  - url: https://edjopato.de/
+",
+    )
+    .unwrap();
+    config.validate_sites().unwrap();
+}
+
+#[test]
+fn sites_use_editor_chains() {
+    let mut config = serde_yaml::from_str::<Config>(
+        "
+editor_chains:
+  cleanup:
+    - css_remove: nav, footer
+    - html_sanitize
+sites:
+  - url: https://edjopato.de/
+    editors:
+      - chain: cleanup
+      - html_textify
+",
+    )
+    .unwrap();
+    config.resolve_editor_chains().unwrap();
+    let names = config.sites[0]
+        .options
+        .editors
+        .iter()
+        .map(Editor::log_name)
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["css_remove", "html_sanitize", "html_textify"]);
+}
+
+#[test]
+#[should_panic = "editor_chains a is invalid"]
+fn unused_editor_chain_cycle_fails() {
+    let mut config = serde_yaml::from_str::<Config>(
+        "
+editor_chains:
+  a:
+    - chain: a
+sites:
+  - url: https://edjopato.de/
+",
+    )
+    .unwrap();
+    config.resolve_editor_chains().unwrap();
+}
+
+#[test]
+#[should_panic = "site[1] https://edjopato.de/two"]
+fn site_with_unknown_editor_chain_fails() {
+    let mut config = serde_yaml::from_str::<Config>(
+        "
+sites:
+  - url: https://edjopato.de/one
+  - url: https://edjopato.de/two
+    editors:
+      - chain: missing
+",
+    )
+    .unwrap();
+    config.resolve_editor_chains().unwrap();
+}
+
+#[test]
+fn site_options_inherit_hosts_and_defaults() {
+    let config = serde_yaml::from_str::<Config>(
+        "
+defaults:
+  ignore_error: true
+  headers:
+    - 'Accept-Language: en'
+    - 'DNT: 1'
+  editors:
+    - html_textify
+hosts:
+  edjopato.de:
+    headers:
+      - 'Accept-Language: de'
+    editors:
+      - html_prettify
+sites:
+  - url: https://edjopato.de/
+  - url: https://edjopato.de/post/
+    headers:
+      - 'Accept-Language: fr'
+    editors:
+      - html_sanitize
+  - url: https://foo.bar/
+  - url: https://foo.bar/other
+    ignore_defaults: true
+",
+    )
+    .unwrap();
+    let sites = config.get_sites();
+    let header = |index: usize, name: &str| {
+        sites[index]
+            .options
+            .headers
+            .get(name)
+            .map(|value| value.to_str().unwrap().to_owned())
+    };
+    let editors = |index: usize| {
+        sites[index]
+            .options
+            .editors
+            .iter()
+            .map(Editor::log_name)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(sites[0].options.ignore_error, Some(true));
+    assert_eq!(header(0, "accept-language").as_deref(), Some("de"));
+    assert_eq!(header(0, "dnt").as_deref(), Some("1"));
+    assert_eq!(editors(0), ["html_prettify"]);
+
+    assert_eq!(header(1, "accept-language").as_deref(), Some("fr"));
+    assert_eq!(editors(1), ["html_sanitize"]);
+
+    assert_eq!(header(2, "accept-language").as_deref(), Some("en"));
+    assert_eq!(editors(2), ["html_textify"]);
+
+    assert_eq!(sites[3].options.ignore_error, None);
+    assert!(sites[3].options.headers.is_empty());
+    assert!(editors(3).is_empty());
+}
+
+#[test]
+fn site_options_override_defaults_with_false() {
+    let config = serde_yaml::from_str::<Config>(
+        "
+defaults:
+  ignore_error: true
+  http1_only: true
+hosts:
+  edjopato.de:
+    http1_only: false
+sites:
+  - url: https://edjopato.de/
+    ignore_error: false
+  - url: https://edjopato.de/post/
+  - url: https://foo.bar/
+",
+    )
+    .unwrap();
+    let sites = config.get_sites();
+    assert_eq!(sites[0].options.ignore_error, Some(false));
+    assert_eq!(sites[0].options.http1_only, Some(false));
+    assert_eq!(sites[1].options.ignore_error, Some(true));
+    assert_eq!(sites[2].options.http1_only, Some(true));
+}
+
 #[test]
 #[should_panic = "site list is empty"]
 fn validate_fails_on_empty_sites_list() {
     let config = Config {
         from: "dummy".to_owned(),
+        retry: None,
+        host_delay: None,
+        host_jitter: None,
+        max_concurrent_requests: None,
+        respect_robots_txt: false,
+        defaults: Defaults::default(),
+        hosts: BTreeMap::new(),
+        editor_chains: EditorChains::default(),
         sites: vec![],
     };
     config.validate_sites().unwrap();
@@ -153,14 +1008,31 @@ fn validate_fails_on_empty_sites_list() {
 fn validate_fails_on_sites_list_with_empty_many() {
     let config = Config {
         from: "dummy".to_owned(),
+        retry: None,
+        host_delay: None,
+        host_jitter: None,
+        max_concurrent_requests: None,
+        respect_robots_txt: false,
+        defaults: Defaults::default(),
+        hosts: BTreeMap::new(),
+        editor_chains: EditorChains::default(),
         sites: vec![SiteEntry {
             url: UrlVariants::Many(vec![]),
+            uninterpolated: None,
             options: Options {
-                accept_invalid_certs: false,
-                http1_only: false,
-                ignore_error: false,
+                accept_invalid_certs: None,
+                http1_only: None,
+                ignore_error: None,
+                ignore_robots_txt: None,
+                ignore_defaults: false,
+                tags: vec![],
+                interval: None,
                 filename: None,
+                retry: None,
+                method: crate::http::Method::Get,
+                body: None,
                 headers: reqwest::header::HeaderMap::new(),
+                session: vec![],
                 editors: vec![],
             },
         }],
@@ -0,0 +1,115 @@
+use std::collections::BTreeMap;
+
+use super::Editor;
+
+/// Replace references to named editor chains with the editors of the chain.
+/// Chains can reference other chains but not themselves.
+pub fn resolve(
+    editors: &[Editor],
+    chains: &BTreeMap<String, Vec<Editor>>,
+) -> anyhow::Result<Vec<Editor>> {
+    resolve_inner(editors, chains, &mut Vec::new())
+}
+
+fn resolve_inner<'c>(
+    editors: &[Editor],
+    chains: &'c BTreeMap<String, Vec<Editor>>,
+    stack: &mut Vec<&'c str>,
+) -> anyhow::Result<Vec<Editor>> {
+    let mut result = Vec::with_capacity(editors.len());
+    for editor in editors {
+        match editor {
+            Editor::Chain(name) => {
+                let (name, chain) = chains
+                    .get_key_value(name)
+                    .ok_or_else(|| anyhow::anyhow!("editor chain {name:?} is not defined"))?;
+                if stack.contains(&name.as_str()) {
+                    anyhow::bail!("editor chain cycle: {} -> {name}", stack.join(" -> "));
+                }
+                stack.push(name);
+                result.extend(resolve_inner(chain, chains, stack)?);
+                stack.pop();
+            }
+            Editor::Rss(rss) => {
+                let mut rss = rss.clone();
+                rss.content_editors = resolve_inner(&rss.content_editors, chains, stack)?;
+                result.push(Editor::Rss(rss));
+            }
+            Editor::CssSort(sort) => {
+                let mut sort = sort.clone();
+                sort.sort_by = resolve_inner(&sort.sort_by, chains, stack)?;
+                result.push(Editor::CssSort(sort));
+            }
+            _ => result.push(editor.clone()),
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+#[track_caller]
+fn case(chains: &str, editors: &str) -> anyhow::Result<Vec<&'static str>> {
+    let chains = serde_json::from_str::<BTreeMap<String, Vec<Editor>>>(chains).unwrap();
+    let editors = serde_json::from_str::<Vec<Editor>>(editors).unwrap();
+    Ok(resolve(&editors, &chains)?
+        .iter()
+        .map(Editor::log_name)
+        .collect())
+}
+
+#[test]
+fn resolves_nested() {
+    let chains = r#"{
+        "cleanup": ["html_sanitize", {"chain": "pretty"}],
+        "pretty": ["html_prettify"]
+    }"#;
+    let editors = r#"[{"chain": "cleanup"}, "html_textify", {"chain": "pretty"}]"#;
+    assert_eq!(
+        case(chains, editors).unwrap(),
+        [
+            "html_sanitize",
+            "html_prettify",
+            "html_textify",
+            "html_prettify"
+        ]
+    );
+}
+
+#[test]
+fn resolves_in_rss_content_editors() {
+    let chains = r#"{"pretty": ["html_prettify"]}"#;
+    let editors = r#"[{"rss": {"content_editors": [{"chain": "pretty"}]}}]"#;
+    let chains = serde_json::from_str::<BTreeMap<String, Vec<Editor>>>(chains).unwrap();
+    let editors = serde_json::from_str::<Vec<Editor>>(editors).unwrap();
+    let resolved = resolve(&editors, &chains).unwrap();
+    let Editor::Rss(rss) = &resolved[0] else {
+        panic!("should be rss");
+    };
+    assert!(matches!(rss.content_editors[..], [Editor::HtmlPrettify]));
+}
+
+#[test]
+fn resolves_in_css_sort_sort_by() {
+    let chains = r#"{"text": ["html_textify"]}"#;
+    let editors = r#"[{"css_sort": {"selector": "li", "sort_by": [{"chain": "text"}]}}]"#;
+    let chains = serde_json::from_str::<BTreeMap<String, Vec<Editor>>>(chains).unwrap();
+    let editors = serde_json::from_str::<Vec<Editor>>(editors).unwrap();
+    let resolved = resolve(&editors, &chains).unwrap();
+    let Editor::CssSort(sort) = &resolved[0] else {
+        panic!("should be css_sort");
+    };
+    assert!(matches!(sort.sort_by[..], [Editor::HtmlTextify]));
+}
+
+#[test]
+#[should_panic = "editor chain \"missing\" is not defined"]
+fn unknown_chain() {
+    case("{}", r#"[{"chain": "missing"}]"#).unwrap();
+}
+
+#[test]
+#[should_panic = "editor chain cycle: a -> b -> a"]
+fn cycle() {
+    let chains = r#"{"a": [{"chain": "b"}], "b": [{"chain": "a"}]}"#;
+    case(chains, r#"[{"chain": "a"}]"#).unwrap();
+}
@@ -0,0 +1,103 @@
+use std::collections::HashSet;
+
+use schemars::JsonSchema;
+use scraper::{Html, Selector};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CssExtract {
+    #[schemars(with = "String")]
+    pub selector: Selector,
+
+    /// Extract the value of this attribute instead of the text content.
+    /// Elements without the attribute are skipped.
+    #[serde(default)]
+    pub attribute: Option<String>,
+
+    /// Remove duplicate lines while keeping the first occurrence
+    #[serde(default)]
+    pub unique: bool,
+
+    /// Sort the lines alphabetically
+    #[serde(default)]
+    pub sort: bool,
+}
+
+impl CssExtract {
+    pub fn apply(&self, html: &str) -> anyhow::Result<String> {
+        let html = Html::parse_document(html);
+        let mut lines = html
+            .select(&self.selector)
+            .filter_map(|element| {
+                self.attribute.as_ref().map_or_else(
+                    || Some(element.text().collect::<String>()),
+                    |attribute| element.value().attr(attribute).map(ToOwned::to_owned),
+                )
+            })
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        anyhow::ensure!(!lines.is_empty(), "extracted nothing");
+        if self.sort {
+            lines.sort();
+        }
+        if self.unique {
+            let mut seen = HashSet::new();
+            lines.retain(|line| seen.insert(line.clone()));
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+const EXAMPLE_HTML: &str = r#"<html><head></head><body>
+<a href="/b.pdf">B
+  document</a>
+<a href="/a.pdf" data-id="1">A</a>
+<a>No link</a>
+<a href="/b.pdf">B again</a>
+</body></html>"#;
+
+#[cfg(test)]
+#[track_caller]
+fn case(attribute: Option<&str>, unique: bool, sort: bool, expected: &str) {
+    let extract = CssExtract {
+        selector: Selector::parse("a").unwrap(),
+        attribute: attribute.map(ToOwned::to_owned),
+        unique,
+        sort,
+    };
+    assert_eq!(extract.apply(EXAMPLE_HTML).unwrap(), expected);
+}
+
+#[test]
+fn text() {
+    case(None, false, false, "B document\nA\nNo link\nB again");
+}
+
+#[test]
+fn attribute() {
+    case(Some("href"), false, false, "/b.pdf\n/a.pdf\n/b.pdf");
+}
+
+#[test]
+fn data_attribute() {
+    case(Some("data-id"), false, false, "1");
+}
+
+#[test]
+fn unique() {
+    case(Some("href"), true, false, "/b.pdf\n/a.pdf");
+}
+
+#[test]
+fn sorted_unique() {
+    case(Some("href"), true, true, "/a.pdf\n/b.pdf");
+}
+
+#[test]
+#[should_panic = "extracted nothing"]
+fn nothing() {
+    case(Some("src"), false, false, "");
+}
@@ -71,17 +71,16 @@ impl CssSort {
     }
 
     fn get_sort_key_from_element(&self, url: &Url, element: &ElementRef) -> String {
-        let content = super::Content {
+        let content = super::Content::Text {
             extension: Some("html"),
             text: element.html(),
         };
-        Editor::apply_many(&self.sort_by, url, content).map_or_else(
-            |error| {
+        Editor::apply_many(&self.sort_by, url, content)
+            .and_then(super::Content::into_text)
+            .unwrap_or_else(|error| {
                 logger::error(&format!("css_sort sort_by failed {error:#}"));
                 String::new()
-            },
-            |content| content.text,
-        )
+            })
     }
 }
 
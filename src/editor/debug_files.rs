@@ -2,7 +2,7 @@ use std::path::Path;
 use std::time::SystemTime;
 
 use super::Content;
-use crate::logger;
+use crate::{logger, secrets};
 
 pub fn debug_files(path: &Path, content: Content) -> anyhow::Result<Content> {
     std::fs::create_dir_all(path)?;
@@ -12,14 +12,17 @@ pub fn debug_files(path: &Path, content: Content) -> anyhow::Result<Content> {
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap_or_default();
     let mut filename = format!("{}-{}", timestamp.as_secs(), timestamp.subsec_nanos());
-    if let Some(extension) = content.extension {
+    if let Some(extension) = content.extension() {
         filename += ".";
         filename += extension;
     }
 
     let file = path.join(filename);
     logger::warn(&format!("debug_files writes {}", file.display()));
-    std::fs::write(file, &content.text)?;
+    match &content {
+        Content::Text { text, .. } => std::fs::write(file, secrets::redact(text).as_bytes())?,
+        Content::Binary { bytes, .. } => std::fs::write(file, bytes)?,
+    }
 
     Ok(content)
 }
@@ -0,0 +1,212 @@
+use core::time::Duration;
+use std::io::{Read, Write as _};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Instant;
+
+use anyhow::Context as _;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use super::Content;
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Exec {
+    pub command: String,
+
+    #[serde(default)]
+    pub args: Vec<String>,
+
+    /// File extension of the output. Defaults to the extension of the input.
+    #[serde(default)]
+    pub extension: Option<Extension>,
+
+    /// The command is killed when it takes longer
+    #[serde(default = "default_timeout", with = "humantime_serde")]
+    #[schemars(with = "String")]
+    pub timeout: Duration,
+}
+
+const fn default_timeout() -> Duration {
+    Duration::from_secs(30)
+}
+
+/// File extension like `md`.
+/// The config is only loaded once so leaking the few extensions is fine.
+#[derive(Debug, Clone, Copy, JsonSchema)]
+#[schemars(with = "String")]
+pub struct Extension(&'static str);
+
+impl<'de> Deserialize<'de> for Extension {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let extension = String::deserialize(deserializer)?;
+        if extension.is_empty() || !extension.chars().all(|char| char.is_ascii_alphanumeric()) {
+            return Err(serde::de::Error::custom(format!(
+                "extension {extension:?} should only contain ASCII letters and digits"
+            )));
+        }
+        Ok(Self(extension.leak()))
+    }
+}
+
+impl Extension {
+    pub const fn as_str(self) -> &'static str {
+        self.0
+    }
+}
+
+impl Exec {
+    /// Pipe the content into the command and use its output.
+    /// Output which is not valid UTF-8 is binary content.
+    pub fn apply(&self, input: &Content) -> anyhow::Result<Content> {
+        let mut child = Command::new(&self.command)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("failed to start {}", self.command))?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let bytes = input.as_bytes().to_vec();
+        // The command might not read its input so ignore write errors and rely on the exit status.
+        // Not joined as a background process of the command might keep stdin open.
+        thread::spawn(move || _ = stdin.write_all(&bytes));
+        let stdout = read_in_background(child.stdout.take().expect("stdout is piped"));
+        let stderr = read_in_background(child.stderr.take().expect("stderr is piped"));
+
+        let deadline = Instant::now() + self.timeout;
+        let timed_out =
+            || anyhow::anyhow!("{} did not finish within {:?}", self.command, self.timeout);
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
+            if Instant::now() > deadline {
+                child.kill()?;
+                child.wait()?;
+                return Err(timed_out());
+            }
+            thread::sleep(Duration::from_millis(10));
+        };
+        // A background process of the command might keep the output open after the command exited
+        let remaining = || deadline.saturating_duration_since(Instant::now());
+        let stdout = stdout
+            .recv_timeout(remaining())
+            .map_err(|_| timed_out())??;
+        let stderr = stderr
+            .recv_timeout(remaining())
+            .map_err(|_| timed_out())??;
+
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&stderr);
+            anyhow::bail!("{} failed with {status}: {}", self.command, stderr.trim());
+        }
+
+        let extension = self
+            .extension
+            .map_or_else(|| input.extension(), |extension| Some(extension.as_str()));
+        Ok(match String::from_utf8(stdout) {
+            Ok(text) => Content::Text { extension, text },
+            Err(err) => Content::Binary {
+                extension,
+                bytes: err.into_bytes(),
+            },
+        })
+    }
+}
+
+fn read_in_background<R: Read + Send + 'static>(
+    mut reader: R,
+) -> mpsc::Receiver<std::io::Result<Vec<u8>>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut buf = Vec::new();
+        let result = reader.read_to_end(&mut buf).map(|_| buf);
+        _ = sender.send(result);
+    });
+    receiver
+}
+
+#[cfg(test)]
+fn exec(command: &str, args: &[&str]) -> Exec {
+    Exec {
+        command: command.to_owned(),
+        args: args.iter().map(ToString::to_string).collect(),
+        extension: None,
+        timeout: default_timeout(),
+    }
+}
+
+#[cfg(test)]
+fn text(text: &str) -> Content {
+    Content::Text {
+        extension: Some("html"),
+        text: text.to_owned(),
+    }
+}
+
+#[test]
+fn pipes_through() {
+    let mut exec = exec("tr", &["a-z", "A-Z"]);
+    exec.extension = Some(Extension("txt"));
+    let result = exec.apply(&text("hello")).unwrap();
+    assert_eq!(result.extension(), Some("txt"));
+    assert_eq!(result.text().unwrap(), "HELLO");
+}
+
+#[test]
+fn keeps_extension() {
+    let result = exec("cat", &[]).apply(&text("hello")).unwrap();
+    assert_eq!(result.extension(), Some("html"));
+}
+
+#[test]
+fn command_not_reading_input() {
+    let result = exec("echo", &["test"]).apply(&text("hello")).unwrap();
+    assert_eq!(result.text().unwrap(), "test\n");
+}
+
+#[test]
+#[should_panic = "sh failed with exit status: 3: went wrong"]
+fn failure_includes_stderr() {
+    exec("sh", &["-c", "echo went wrong >&2; exit 3"])
+        .apply(&text("hello"))
+        .unwrap();
+}
+
+#[test]
+#[should_panic = "sleep did not finish within 100ms"]
+fn timeout() {
+    let mut exec = exec("sleep", &["5"]);
+    exec.timeout = Duration::from_millis(100);
+    exec.apply(&text("hello")).unwrap();
+}
+
+#[test]
+fn timeout_includes_output_of_background_processes() {
+    let mut exec = exec("sh", &["-c", "sleep 5 & echo hi"]);
+    exec.timeout = Duration::from_millis(200);
+    let start = Instant::now();
+    let Err(error) = exec.apply(&text("hello")) else {
+        panic!("should time out");
+    };
+    assert_eq!(error.to_string(), "sh did not finish within 200ms");
+    assert!(start.elapsed() < Duration::from_secs(2));
+}
+
+#[test]
+#[should_panic = "failed to start website-stalker-does-not-exist"]
+fn missing_command() {
+    exec("website-stalker-does-not-exist", &[])
+        .apply(&text("hello"))
+        .unwrap();
+}
+
+#[test]
+#[should_panic = "should only contain ASCII letters and digits"]
+fn invalid_extension() {
+    serde_json::from_str::<Exec>(r#"{"command": "cat", "extension": ".md"}"#).unwrap();
+}
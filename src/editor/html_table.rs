@@ -0,0 +1,280 @@
+use schemars::JsonSchema;
+use scraper::{ElementRef, Html, Selector};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct HtmlTable {
+    /// Select the tables to be converted. Defaults to all tables.
+    #[serde(default = "all_tables")]
+    #[schemars(with = "String")]
+    pub selector: Selector,
+
+    #[serde(default)]
+    pub format: TableFormat,
+
+    /// Only keep these columns in the given order. Defaults to all columns.
+    #[serde(default)]
+    pub columns: Vec<Column>,
+
+    /// Sort the rows below the header by the text of this column
+    #[serde(default)]
+    pub sort_by: Option<Column>,
+}
+
+fn all_tables() -> Selector {
+    Selector::parse("table").expect("valid selector")
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TableFormat {
+    Csv,
+    #[default]
+    Markdown,
+}
+
+impl TableFormat {
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// Column by its header text or its index starting at 0
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum Column {
+    Index(usize),
+    Header(String),
+}
+
+impl Column {
+    fn find(&self, header: &[String]) -> anyhow::Result<usize> {
+        match self {
+            Self::Index(index) if *index < header.len() => Ok(*index),
+            Self::Index(index) => anyhow::bail!(
+                "column {index} does not exist in a table with {} columns",
+                header.len()
+            ),
+            Self::Header(name) => header
+                .iter()
+                .position(|cell| cell == name)
+                .ok_or_else(|| anyhow::anyhow!("column {name:?} does not exist in {header:?}")),
+        }
+    }
+}
+
+impl HtmlTable {
+    pub fn apply(&self, html: &str) -> anyhow::Result<String> {
+        let html = Html::parse_document(html);
+        let tables = html
+            .select(&self.selector)
+            .filter(|element| element.value().name() == "table")
+            .map(|table| self.convert(table))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        anyhow::ensure!(!tables.is_empty(), "selected no table");
+        Ok(tables.join("\n\n"))
+    }
+
+    fn convert(&self, table: ElementRef) -> anyhow::Result<String> {
+        let mut rows = rows(table);
+        anyhow::ensure!(!rows.is_empty(), "table has no rows");
+        let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+        for row in &mut rows {
+            row.resize(width, String::new());
+        }
+
+        if let Some(sort_by) = &self.sort_by {
+            let index = sort_by.find(&rows[0])?;
+            rows[1..].sort_by(|one, other| one[index].cmp(&other[index]));
+        }
+
+        if !self.columns.is_empty() {
+            let indices = self
+                .columns
+                .iter()
+                .map(|column| column.find(&rows[0]))
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            rows = rows
+                .into_iter()
+                .map(|row| indices.iter().map(|index| row[*index].clone()).collect())
+                .collect();
+        }
+
+        Ok(match self.format {
+            TableFormat::Csv => csv(&rows),
+            TableFormat::Markdown => markdown(&rows),
+        })
+    }
+}
+
+/// Text of the cells of all rows of the table without the rows of nested tables.
+/// Cells spanning multiple columns are repeated to keep the columns aligned.
+fn rows(table: ElementRef) -> Vec<Vec<String>> {
+    let tr = Selector::parse("tr").expect("valid selector");
+    table
+        .select(&tr)
+        .filter(|row| {
+            row.ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|element| element.value().name() == "table")
+                .is_some_and(|closest| closest.id() == table.id())
+        })
+        .map(|row| {
+            let mut cells = Vec::new();
+            for cell in row
+                .child_elements()
+                .filter(|cell| matches!(cell.value().name(), "td" | "th"))
+            {
+                let text = cell.text().collect::<String>();
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                let colspan = cell
+                    .value()
+                    .attr("colspan")
+                    .and_then(|colspan| colspan.parse::<usize>().ok())
+                    .unwrap_or(1)
+                    .clamp(1, 100);
+                for _ in 0..colspan {
+                    cells.push(text.clone());
+                }
+            }
+            cells
+        })
+        .filter(|cells| !cells.is_empty())
+        .collect()
+}
+
+fn csv(rows: &[Vec<String>]) -> String {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| {
+                    if cell.contains([',', '"', '\n']) {
+                        format!("\"{}\"", cell.replace('"', "\"\""))
+                    } else {
+                        cell.clone()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Aligned Markdown table with the first row as header
+fn markdown(rows: &[Vec<String>]) -> String {
+    let rows = rows
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.replace('|', "\\|"))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let widths = (0..rows[0].len())
+        .map(|index| {
+            rows.iter()
+                .map(|row| row[index].chars().count())
+                .max()
+                .unwrap_or_default()
+                .max(3)
+        })
+        .collect::<Vec<_>>();
+    let line = |cells: &[String]| {
+        let cells = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{cell:width$}"))
+            .collect::<Vec<_>>();
+        format!("| {} |", cells.join(" | "))
+    };
+    let separator = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<_>>();
+
+    let mut lines = vec![line(&rows[0]), line(&separator)];
+    lines.extend(rows[1..].iter().map(|row| line(row)));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+const EXAMPLE_HTML: &str = r#"<html><head></head><body>
+<table id="prices">
+  <thead><tr><th>Product</th><th>Price</th><th>Stock</th></tr></thead>
+  <tbody>
+    <tr><td>Pear</td><td>2,50 €</td><td>12</td></tr>
+    <tr><td>Apple | green</td><td>1 €</td><td>3</td></tr>
+    <tr><td colspan="3">Sold out: <table><tr><td>nested</td></tr></table></td></tr>
+  </tbody>
+</table>
+</body></html>"#;
+
+#[cfg(test)]
+#[track_caller]
+fn case(format: TableFormat, columns: Vec<Column>, sort_by: Option<Column>, expected: &str) {
+    let table = HtmlTable {
+        selector: Selector::parse("#prices").unwrap(),
+        format,
+        columns,
+        sort_by,
+    };
+    assert_eq!(table.apply(EXAMPLE_HTML).unwrap(), expected);
+}
+
+#[test]
+fn markdown_table() {
+    case(
+        TableFormat::Markdown,
+        Vec::new(),
+        None,
+        r"| Product          | Price            | Stock            |
+| ---------------- | ---------------- | ---------------- |
+| Pear             | 2,50 €           | 12               |
+| Apple \| green   | 1 €              | 3                |
+| Sold out: nested | Sold out: nested | Sold out: nested |",
+    );
+}
+
+#[test]
+fn csv_table() {
+    case(
+        TableFormat::Csv,
+        Vec::new(),
+        None,
+        "Product,Price,Stock\nPear,\"2,50 €\",12\nApple | green,1 €,3\nSold out: nested,Sold out: nested,Sold out: nested",
+    );
+}
+
+#[test]
+fn columns_and_sorting() {
+    case(
+        TableFormat::Csv,
+        vec![Column::Header("Price".to_owned()), Column::Index(0)],
+        Some(Column::Header("Product".to_owned())),
+        "Price,Product\n1 €,Apple | green\n\"2,50 €\",Pear\nSold out: nested,Sold out: nested",
+    );
+}
+
+#[test]
+#[should_panic = "column \"Amount\" does not exist"]
+fn unknown_column() {
+    case(
+        TableFormat::Csv,
+        vec![Column::Header("Amount".to_owned())],
+        None,
+        "",
+    );
+}
+
+#[test]
+fn deserialize_columns() {
+    let table =
+        serde_json::from_str::<HtmlTable>(r#"{"format": "csv", "columns": [0, "Price"]}"#).unwrap();
+    assert!(matches!(table.columns[0], Column::Index(0)));
+    assert!(matches!(&table.columns[1], Column::Header(name) if name == "Price"));
+}
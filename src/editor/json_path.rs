@@ -0,0 +1,130 @@
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// Pattern matching the path of a value within JSON.
+///
+/// Segments are separated by `.`.
+/// `*` matches any single key and `**` matches any amount of keys.
+/// Arrays are traversed transparently so `items.id` matches the `id` of every item.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(try_from = "String")]
+pub struct JsonPath(#[schemars(with = "String")] Vec<Segment>);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Key(String),
+    Any,
+    AnyDepth,
+}
+
+impl TryFrom<String> for JsonPath {
+    type Error = String;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        let trimmed = pattern.strip_prefix('.').unwrap_or(&pattern);
+        if trimmed.is_empty() {
+            return Ok(Self(Vec::new()));
+        }
+        trimmed
+            .split('.')
+            .map(|segment| match segment {
+                "" => Err(format!("json path {pattern:?} contains an empty segment")),
+                "*" => Ok(Segment::Any),
+                "**" => Ok(Segment::AnyDepth),
+                key => Ok(Segment::Key(key.to_owned())),
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+impl JsonPath {
+    /// Check whether the path of object keys matches the pattern
+    pub fn matches(&self, path: &[&str]) -> bool {
+        fn inner(pattern: &[Segment], path: &[&str]) -> bool {
+            match (pattern.split_first(), path.split_first()) {
+                (None, _) => path.is_empty(),
+                (Some((Segment::AnyDepth, rest)), _) => {
+                    inner(rest, path) || (!path.is_empty() && inner(pattern, &path[1..]))
+                }
+                (Some(_), None) => false,
+                (Some((Segment::Any, rest)), Some((_, path))) => inner(rest, path),
+                (Some((Segment::Key(key), rest)), Some((first, path))) => {
+                    key == first && inner(rest, path)
+                }
+            }
+        }
+        inner(&self.0, path)
+    }
+
+    /// Get the value at the path. Only works with paths without wildcards.
+    pub fn get<'value>(
+        &self,
+        value: &'value serde_json::Value,
+    ) -> Option<&'value serde_json::Value> {
+        let mut current = value;
+        for segment in &self.0 {
+            let Segment::Key(key) = segment else {
+                return None;
+            };
+            current = current.get(key)?;
+        }
+        Some(current)
+    }
+}
+
+#[cfg(test)]
+fn path(pattern: &str) -> JsonPath {
+    JsonPath::try_from(pattern.to_owned()).unwrap()
+}
+
+#[test]
+fn matches_key() {
+    let pattern = path("foo.bar");
+    assert!(pattern.matches(&["foo", "bar"]));
+    assert!(!pattern.matches(&["foo"]));
+    assert!(!pattern.matches(&["foo", "bar", "baz"]));
+    assert!(!pattern.matches(&["bar", "bar"]));
+}
+
+#[test]
+fn leading_dot_is_optional() {
+    assert_eq!(path(".foo.bar"), path("foo.bar"));
+}
+
+#[test]
+fn matches_any() {
+    let pattern = path("*.bar");
+    assert!(pattern.matches(&["foo", "bar"]));
+    assert!(pattern.matches(&["baz", "bar"]));
+    assert!(!pattern.matches(&["bar"]));
+}
+
+#[test]
+fn matches_any_depth() {
+    let pattern = path("**.updatedAt");
+    assert!(pattern.matches(&["updatedAt"]));
+    assert!(pattern.matches(&["foo", "updatedAt"]));
+    assert!(pattern.matches(&["foo", "bar", "updatedAt"]));
+    assert!(!pattern.matches(&["foo", "updatedAt", "bar"]));
+}
+
+#[test]
+fn empty_matches_root() {
+    let pattern = path("");
+    assert!(pattern.matches(&[]));
+    assert!(!pattern.matches(&["foo"]));
+}
+
+#[test]
+#[should_panic = "contains an empty segment"]
+fn empty_segment_fails() {
+    path("foo..bar");
+}
+
+#[test]
+fn get_value() {
+    let value = serde_json::json!({"foo": {"bar": 42}});
+    assert_eq!(path("foo.bar").get(&value), Some(&serde_json::json!(42)));
+    assert_eq!(path("foo.baz").get(&value), None);
+}
@@ -0,0 +1,173 @@
+use jaq_core::load::{Arena, File, Loader};
+use jaq_core::{Ctx, Vars, data, unwrap_valr};
+use jaq_json::Val;
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+type Filter = jaq_core::Filter<data::JustLut<Val>>;
+
+/// A [jq](https://jqlang.org/) like expression.
+/// It is checked to be valid when the config is loaded.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(try_from = "String")]
+pub struct JsonQuery(#[schemars(with = "String")] String);
+
+impl TryFrom<String> for JsonQuery {
+    type Error = String;
+
+    fn try_from(query: String) -> Result<Self, Self::Error> {
+        compile(&query)?;
+        Ok(Self(query))
+    }
+}
+
+fn compile(query: &str) -> Result<Filter, String> {
+    let defs = jaq_core::defs()
+        .chain(jaq_std::defs())
+        .chain(jaq_json::defs());
+    let funs = jaq_core::funs()
+        .chain(jaq_std::funs())
+        .chain(jaq_json::funs());
+    let arena = Arena::default();
+    let modules = Loader::new(defs)
+        .load(
+            &arena,
+            File {
+                code: query,
+                path: (),
+            },
+        )
+        .map_err(|errors| {
+            let errors = errors
+                .into_iter()
+                .map(|(_, error)| format!("{error:?}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("json_query {query:?} could not be parsed: {errors}")
+        })?;
+    jaq_core::Compiler::default()
+        .with_funs(funs)
+        .compile(modules)
+        .map_err(|errors| {
+            let errors = errors
+                .into_iter()
+                .flat_map(|(_, errors)| errors)
+                .map(|(name, undefined)| format!("{undefined:?} {name} is undefined"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("json_query {query:?} could not be compiled: {errors}")
+        })
+}
+
+impl JsonQuery {
+    /// Every result is a line of compact JSON like `jq --compact-output` does
+    pub fn apply(&self, json: &str) -> anyhow::Result<String> {
+        let filter = compile(&self.0).map_err(anyhow::Error::msg)?;
+        let input = jaq_json::read::parse_single(json.as_bytes())
+            .map_err(|err| anyhow::anyhow!("input is not valid JSON: {err}"))?;
+        let ctx = Ctx::<data::JustLut<Val>>::new(&filter.lut, Vars::new([]));
+        let mut lines = Vec::new();
+        for value in filter.id.run((ctx, input)).map(unwrap_valr) {
+            let value = value.map_err(|err| anyhow::anyhow!("json_query failed: {err}"))?;
+            lines.push(value.to_string());
+        }
+        Ok(lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+#[track_caller]
+fn case(input: &str, query: &str, expected: &str) {
+    use std::io::Write as _;
+
+    let actual = JsonQuery::try_from(query.to_owned())
+        .unwrap()
+        .apply(input)
+        .unwrap();
+    assert_eq!(actual, expected);
+
+    let process = std::process::Command::new("jq")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .arg("--compact-output")
+        .arg(query)
+        .spawn()
+        .expect("jq should be spawnable");
+    process
+        .stdin
+        .as_ref()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .expect("jq process should get input via stdin");
+    let output = process.wait_with_output().expect("Should wait for jq");
+
+    if !output.status.success() || !output.stderr.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        panic!("jq unsuccessful:\n{stderr}");
+    }
+
+    let jq_stdout = String::from_utf8(output.stdout).expect("jq stdout should be Utf-8");
+    assert_eq!(jq_stdout.trim(), expected);
+}
+
+#[test]
+fn iterate() {
+    case("[1, 2, 3]", ".[]", "1\n2\n3");
+}
+
+#[test]
+fn object_construction() {
+    case(
+        r#"{"name": "foo", "id": 42, "requestId": "abc"}"#,
+        "{name, id}",
+        r#"{"name":"foo","id":42}"#,
+    );
+}
+
+#[test]
+fn select_and_map() {
+    case(
+        r#"[{"a": 1}, {"a": 2}, {"a": 3}]"#,
+        "map(select(.a >= 2) | .a)",
+        "[2,3]",
+    );
+}
+
+#[test]
+fn sort_by() {
+    case(
+        r#"[{"a": 3}, {"a": 1}, {"a": 2}]"#,
+        "sort_by(.a)",
+        r#"[{"a":1},{"a":2},{"a":3}]"#,
+    );
+}
+
+#[test]
+fn del_volatile_fields() {
+    case(
+        r#"{"data": [{"id": 1, "updatedAt": "now"}], "requestId": "abc"}"#,
+        "del(.requestId, .data[].updatedAt)",
+        r#"{"data":[{"id":1}]}"#,
+    );
+}
+
+#[test]
+#[should_panic = "could not be parsed"]
+fn invalid_query() {
+    JsonQuery::try_from(".[".to_owned()).unwrap();
+}
+
+#[test]
+#[should_panic = "could not be compiled"]
+fn unknown_function() {
+    JsonQuery::try_from("does_not_exist".to_owned()).unwrap();
+}
+
+#[test]
+#[should_panic = "json_query failed"]
+fn runtime_error() {
+    JsonQuery::try_from(".[]".to_owned())
+        .unwrap()
+        .apply("42")
+        .unwrap();
+}
@@ -0,0 +1,69 @@
+use serde_json::Value;
+
+use super::json_path::JsonPath;
+
+/// Remove all object keys whose path matches the pattern
+pub fn remove_keys(pattern: &JsonPath, json: &str) -> anyhow::Result<String> {
+    fn inner(pattern: &JsonPath, value: &mut Value, path: &mut Vec<String>) {
+        match value {
+            Value::Array(values) => {
+                for value in values {
+                    inner(pattern, value, path);
+                }
+            }
+            Value::Object(map) => {
+                map.retain(|key, _| {
+                    path.push(key.clone());
+                    let keys = path.iter().map(String::as_str).collect::<Vec<_>>();
+                    let matches = pattern.matches(&keys);
+                    path.pop();
+                    !matches
+                });
+                for (key, value) in map.iter_mut() {
+                    path.push(key.clone());
+                    inner(pattern, value, path);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut value: Value = serde_json::from_str(json)?;
+    inner(pattern, &mut value, &mut Vec::new());
+    Ok(serde_json::to_string(&value)?)
+}
+
+#[cfg(test)]
+#[track_caller]
+fn case(pattern: &str, input: &str, expected: &str) {
+    let pattern = JsonPath::try_from(pattern.to_owned()).unwrap();
+    assert_eq!(remove_keys(&pattern, input).unwrap(), expected);
+}
+
+#[test]
+fn removes_everywhere() {
+    case(
+        "**.updatedAt",
+        r#"{"updatedAt": 1, "items": [{"id": 1, "updatedAt": 2}], "nested": {"deep": {"updatedAt": 3}}}"#,
+        r#"{"items":[{"id":1}],"nested":{"deep":{}}}"#,
+    );
+}
+
+#[test]
+fn removes_specific() {
+    case(
+        "meta.requestId",
+        r#"{"requestId": 1, "meta": {"requestId": 2, "page": 1}}"#,
+        r#"{"meta":{"page":1},"requestId":1}"#,
+    );
+}
+
+#[test]
+fn removes_with_wildcard() {
+    case(
+        "*.id",
+        r#"{"id": 0, "a": {"id": 1, "b": {"id": 2}}}"#,
+        r#"{"a":{"b":{"id":2}},"id":0}"#,
+    );
+}
@@ -0,0 +1,161 @@
+use core::cmp::Ordering;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::json_path::JsonPath;
+
+/// Sort the keys of all objects recursively.
+/// `serde_json` is used without `preserve_order` so its objects are always sorted by their keys.
+pub fn sort_keys(json: &str) -> anyhow::Result<String> {
+    let value: Value = serde_json::from_str(json)?;
+    Ok(serde_json::to_string(&value)?)
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonSortArrays {
+    /// Arrays at matching paths are sorted. Defaults to all arrays.
+    #[serde(default = "all_arrays")]
+    pub path: JsonPath,
+
+    /// Sort the elements by the value at this path within them instead of the whole element
+    #[serde(default)]
+    pub by: Option<JsonPath>,
+}
+
+fn all_arrays() -> JsonPath {
+    JsonPath::try_from("**".to_owned()).expect("valid path")
+}
+
+impl JsonSortArrays {
+    pub fn apply(&self, json: &str) -> anyhow::Result<String> {
+        let mut value: Value = serde_json::from_str(json)?;
+        self.sort(&mut value, &mut Vec::new());
+        Ok(serde_json::to_string(&value)?)
+    }
+
+    fn sort(&self, value: &mut Value, path: &mut Vec<String>) {
+        match value {
+            Value::Array(values) => {
+                for value in values.iter_mut() {
+                    self.sort(value, path);
+                }
+                let keys = path.iter().map(String::as_str).collect::<Vec<_>>();
+                if self.path.matches(&keys) {
+                    values.sort_by(|one, other| compare(self.sort_key(one), self.sort_key(other)));
+                }
+            }
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    path.push(key.clone());
+                    self.sort(value, path);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn sort_key<'value>(&self, value: &'value Value) -> &'value Value {
+        self.by
+            .as_ref()
+            .map_or(Some(value), |by| by.get(value))
+            .unwrap_or(&Value::Null)
+    }
+}
+
+/// Order like `jq` does: null, false, true, numbers, strings, arrays, objects
+fn compare(one: &Value, other: &Value) -> Ordering {
+    const fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(false) => 1,
+            Value::Bool(true) => 2,
+            Value::Number(_) => 3,
+            Value::String(_) => 4,
+            Value::Array(_) => 5,
+            Value::Object(_) => 6,
+        }
+    }
+
+    match (one, other) {
+        (Value::Number(one), Value::Number(other)) => one
+            .as_f64()
+            .partial_cmp(&other.as_f64())
+            .unwrap_or(Ordering::Equal),
+        (Value::String(one), Value::String(other)) => one.cmp(other),
+        (Value::Array(one), Value::Array(other)) => one
+            .iter()
+            .zip(other)
+            .map(|(one, other)| compare(one, other))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or_else(|| one.len().cmp(&other.len())),
+        (Value::Object(_), Value::Object(_)) => one.to_string().cmp(&other.to_string()),
+        _ => rank(one).cmp(&rank(other)),
+    }
+}
+
+#[test]
+fn sorts_keys_recursively() {
+    let result = sort_keys(r#"{"b": [{"d": 1, "c": 2}], "a": {"z": 1, "y": 2}}"#).unwrap();
+    assert_eq!(result, r#"{"a":{"y":2,"z":1},"b":[{"c":2,"d":1}]}"#);
+}
+
+#[test]
+fn sorts_keys_of_json_query_output() {
+    let query = super::json_query::JsonQuery::try_from("del(.x)".to_owned()).unwrap();
+    let unsorted = query.apply(r#"{"x":0,"b":1,"a":{"z":1,"y":2}}"#).unwrap();
+    assert_eq!(unsorted, r#"{"a":{"z":1,"y":2},"b":1}"#);
+    assert_eq!(
+        sort_keys(&unsorted).unwrap(),
+        r#"{"a":{"y":2,"z":1},"b":1}"#
+    );
+}
+
+#[cfg(test)]
+#[track_caller]
+fn case(path: &str, by: Option<&str>, input: &str, expected: &str) {
+    let sort = JsonSortArrays {
+        path: JsonPath::try_from(path.to_owned()).unwrap(),
+        by: by.map(|by| JsonPath::try_from(by.to_owned()).unwrap()),
+    };
+    assert_eq!(sort.apply(input).unwrap(), expected);
+}
+
+#[test]
+fn sorts_all_arrays() {
+    case(
+        "**",
+        None,
+        r#"[3, 1, {"a": ["b", "a"]}, null, "x"]"#,
+        r#"[null,1,3,"x",{"a":["a","b"]}]"#,
+    );
+}
+
+#[test]
+fn sorts_by_key() {
+    case(
+        "items",
+        Some("id"),
+        r#"{"items": [{"id": 2}, {"id": 10}, {"id": 1}], "other": [2, 1]}"#,
+        r#"{"items":[{"id":1},{"id":2},{"id":10}],"other":[2,1]}"#,
+    );
+}
+
+#[test]
+fn sorts_by_nested_key() {
+    case(
+        "",
+        Some("user.name"),
+        r#"[{"user": {"name": "b"}}, {"user": {"name": "a"}}, {}]"#,
+        r#"[{},{"user":{"name":"a"}},{"user":{"name":"b"}}]"#,
+    );
+}
+
+#[test]
+fn deserialize_defaults_to_all_arrays() {
+    let sort = serde_json::from_str::<JsonSortArrays>(r#"{"by": "id"}"#).unwrap();
+    assert!(sort.path.matches(&["foo", "bar"]));
+}
@@ -0,0 +1,73 @@
+use std::collections::HashSet;
+
+use regex::Regex;
+
+pub fn sort(text: &str) -> String {
+    let mut lines = text.lines().collect::<Vec<_>>();
+    lines.sort_unstable();
+    lines.join("\n")
+}
+
+/// Remove duplicate lines while keeping the first occurrence
+pub fn unique(text: &str) -> String {
+    let mut seen = HashSet::new();
+    text.lines()
+        .filter(|line| seen.insert(*line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Keep only the lines matching the pattern or only the ones not matching it
+pub fn filter(pattern: &Regex, keep_matching: bool, text: &str) -> String {
+    text.lines()
+        .filter(|line| pattern.is_match(line) == keep_matching)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+pub fn head(amount: usize, text: &str) -> String {
+    text.lines().take(amount).collect::<Vec<_>>().join("\n")
+}
+
+pub fn tail(amount: usize, text: &str) -> String {
+    let lines = text.lines().collect::<Vec<_>>();
+    lines[lines.len().saturating_sub(amount)..].join("\n")
+}
+
+#[cfg(test)]
+const EXAMPLE: &str = "b\na\nc\na\nb";
+
+#[test]
+fn sorts() {
+    assert_eq!(sort(EXAMPLE), "a\na\nb\nb\nc");
+}
+
+#[test]
+fn unique_keeps_first() {
+    assert_eq!(unique(EXAMPLE), "b\na\nc");
+}
+
+#[test]
+fn keeps_matching() {
+    let pattern = Regex::new("^[ab]$").unwrap();
+    assert_eq!(filter(&pattern, true, EXAMPLE), "b\na\na\nb");
+}
+
+#[test]
+fn drops_matching() {
+    let pattern = Regex::new("^[ab]$").unwrap();
+    assert_eq!(filter(&pattern, false, EXAMPLE), "c");
+}
+
+#[test]
+fn heads() {
+    assert_eq!(head(2, EXAMPLE), "b\na");
+    assert_eq!(head(10, EXAMPLE), EXAMPLE);
+}
+
+#[test]
+fn tails() {
+    assert_eq!(tail(2, EXAMPLE), "a\nb");
+    assert_eq!(tail(10, EXAMPLE), EXAMPLE);
+    assert_eq!(tail(0, EXAMPLE), "");
+}
@@ -5,25 +5,84 @@ use schemars::JsonSchema;
 use serde::Deserialize;
 use url::Url;
 
+pub mod chain;
+pub mod css_extract;
 pub mod css_flatten;
 pub mod css_remove;
 pub mod css_selector;
 pub mod css_sort;
 pub mod css_tag_replace;
 pub mod debug_files;
+pub mod exec;
 pub mod html_markdown;
 pub mod html_pretty;
 pub mod html_sanitize;
+pub mod html_table;
 pub mod html_text;
 pub mod html_url;
+pub mod json_path;
 pub mod json_prettify;
+pub mod json_query;
+pub mod json_remove;
 pub mod json_simple_select;
+pub mod json_sort;
+pub mod lines;
+pub mod pdf_text;
 pub mod regex_replacer;
 pub mod rss;
+pub mod script;
+pub mod xml;
+pub mod xml_pretty;
+pub mod xml_selector;
+pub mod xpath;
 
-pub struct Content {
-    pub extension: Option<&'static str>,
-    pub text: String,
+pub enum Content {
+    Text {
+        extension: Option<&'static str>,
+        text: String,
+    },
+    /// Content like PDFs or images which is stored as is
+    Binary {
+        extension: Option<&'static str>,
+        bytes: Vec<u8>,
+    },
+}
+
+impl Content {
+    pub const fn extension(&self) -> Option<&'static str> {
+        match self {
+            Self::Text { extension, .. } | Self::Binary { extension, .. } => *extension,
+        }
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            Self::Text { text, .. } => text.as_bytes(),
+            Self::Binary { bytes, .. } => bytes,
+        }
+    }
+
+    /// The text of the content. Binary content can not be edited as text.
+    pub fn text(&self) -> anyhow::Result<&str> {
+        match self {
+            Self::Text { text, .. } => Ok(text),
+            Self::Binary { extension, .. } => Err(binary_error(*extension)),
+        }
+    }
+
+    pub fn into_text(self) -> anyhow::Result<String> {
+        match self {
+            Self::Text { text, .. } => Ok(text),
+            Self::Binary { extension, .. } => Err(binary_error(extension)),
+        }
+    }
+}
+
+fn binary_error(extension: Option<&'static str>) -> anyhow::Error {
+    anyhow::anyhow!(
+        "the content is binary ({}) and can not be edited as text",
+        extension.unwrap_or("unknown type")
+    )
 }
 
 /// # Editor
@@ -32,103 +91,237 @@ pub struct Content {
 #[serde(rename_all = "snake_case")]
 #[serde(deny_unknown_fields)]
 pub enum Editor {
+    /// Use the editors of the named chain defined in `editor_chains`
+    Chain(String),
+    CssExtract(css_extract::CssExtract),
     CssFlatten(#[schemars(with = "String")] scraper::Selector),
     CssRemove(#[schemars(with = "String")] scraper::Selector),
     CssSelect(#[schemars(with = "String")] scraper::Selector),
     CssSort(css_sort::CssSort),
     CssTagReplace(css_tag_replace::CssTagReplace),
     DebugFiles(PathBuf),
+    Exec(exec::Exec),
     HtmlMarkdownify,
     HtmlPrettify,
     HtmlSanitize,
+    HtmlTable(html_table::HtmlTable),
     HtmlTextify,
     HtmlUrlCanonicalize,
     JsonPrettify,
+    JsonQuery(json_query::JsonQuery),
+    JsonRemoveKeys(json_path::JsonPath),
     JsonSimpleSelect(String),
+    JsonSortArrays(json_sort::JsonSortArrays),
+    JsonSortKeys,
+    LinesDrop(
+        #[serde(deserialize_with = "regex_replacer::deserialize_regex")]
+        #[schemars(with = "String")]
+        regex::Regex,
+    ),
+    LinesHead(usize),
+    LinesKeep(
+        #[serde(deserialize_with = "regex_replacer::deserialize_regex")]
+        #[schemars(with = "String")]
+        regex::Regex,
+    ),
+    LinesSort,
+    LinesTail(usize),
+    LinesUnique,
+    PdfTextify,
     RegexReplace(regex_replacer::RegexReplacer),
     Rss(rss::Rss),
+    Script(script::Script),
+    XmlPrettify,
+    XmlSelect(#[schemars(with = "String")] scraper::Selector),
+    XpathRemove(xpath::XPath),
+    XpathSelect(xpath::XPath),
 }
 
 impl Editor {
     pub const fn log_name(&self) -> &'static str {
         match self {
+            Self::Chain(_) => "chain",
+            Self::CssExtract(_) => "css_extract",
             Self::CssFlatten(_) => "css_flatten",
             Self::CssRemove(_) => "css_remove",
             Self::CssSelect(_) => "css_select",
             Self::CssSort(_) => "css_sort",
             Self::CssTagReplace(_) => "css_tag_replace",
             Self::DebugFiles(_) => "debug_files",
+            Self::Exec(_) => "exec",
             Self::HtmlMarkdownify => "html_markdownify",
             Self::HtmlPrettify => "html_prettify",
             Self::HtmlSanitize => "html_sanitize",
+            Self::HtmlTable(_) => "html_table",
             Self::HtmlTextify => "html_textify",
             Self::HtmlUrlCanonicalize => "html_url_canonicalize",
             Self::JsonPrettify => "json_prettify",
+            Self::JsonQuery(_) => "json_query",
+            Self::JsonRemoveKeys(_) => "json_remove_keys",
             Self::JsonSimpleSelect(_) => "json_simple_select",
+            Self::JsonSortArrays(_) => "json_sort_arrays",
+            Self::JsonSortKeys => "json_sort_keys",
+            Self::LinesDrop(_) => "lines_drop",
+            Self::LinesHead(_) => "lines_head",
+            Self::LinesKeep(_) => "lines_keep",
+            Self::LinesSort => "lines_sort",
+            Self::LinesTail(_) => "lines_tail",
+            Self::LinesUnique => "lines_unique",
+            Self::PdfTextify => "pdf_textify",
             Self::RegexReplace(_) => "regex_replace",
             Self::Rss(_) => "rss",
+            Self::Script(_) => "script",
+            Self::XmlPrettify => "xml_prettify",
+            Self::XmlSelect(_) => "xml_select",
+            Self::XpathRemove(_) => "xpath_remove",
+            Self::XpathSelect(_) => "xpath_select",
         }
     }
 
+    #[expect(clippy::too_many_lines, reason = "one arm per editor")]
     fn apply(&self, url: &Url, input: Content) -> anyhow::Result<Content> {
         match &self {
-            Self::CssFlatten(selector) => Ok(Content {
+            Self::Chain(name) => {
+                anyhow::bail!("editor chain {name:?} should have been resolved on config load")
+            }
+            Self::CssExtract(extract) => Ok(Content::Text {
+                extension: Some("txt"),
+                text: extract.apply(input.text()?)?,
+            }),
+            Self::CssFlatten(selector) => Ok(Content::Text {
                 extension: Some("html"),
-                text: css_flatten::apply(selector, &input.text),
+                text: css_flatten::apply(selector, input.text()?),
             }),
-            Self::CssRemove(selector) => Ok(Content {
+            Self::CssRemove(selector) => Ok(Content::Text {
                 extension: Some("html"),
-                text: css_remove::apply(selector, &input.text),
+                text: css_remove::apply(selector, input.text()?),
             }),
-            Self::CssSelect(selector) => Ok(Content {
+            Self::CssSelect(selector) => Ok(Content::Text {
                 extension: Some("html"),
-                text: css_selector::apply(selector, &input.text)?,
+                text: css_selector::apply(selector, input.text()?)?,
             }),
-            Self::CssSort(sort) => Ok(Content {
+            Self::CssSort(sort) => Ok(Content::Text {
                 extension: Some("html"),
-                text: sort.apply(url, &input.text),
+                text: sort.apply(url, input.text()?),
             }),
-            Self::CssTagReplace(replace) => Ok(Content {
+            Self::CssTagReplace(replace) => Ok(Content::Text {
                 extension: Some("html"),
-                text: replace.apply(&input.text)?,
+                text: replace.apply(input.text()?)?,
             }),
             Self::DebugFiles(path) => debug_files::debug_files(path, input),
-            Self::HtmlMarkdownify => Ok(Content {
+            Self::Exec(exec) => exec.apply(&input),
+            Self::HtmlMarkdownify => Ok(Content::Text {
                 extension: Some("md"),
-                text: html_markdown::markdownify(&input.text)?,
+                text: html_markdown::markdownify(input.text()?)?,
             }),
-            Self::HtmlPrettify => Ok(Content {
+            Self::HtmlPrettify => Ok(Content::Text {
                 extension: Some("html"),
-                text: html_pretty::prettify(&input.text)?,
+                text: html_pretty::prettify(input.text()?)?,
             }),
-            Self::HtmlSanitize => Ok(Content {
+            Self::HtmlSanitize => Ok(Content::Text {
                 extension: Some("html"),
-                text: html_sanitize::sanitize(&input.text),
+                text: html_sanitize::sanitize(input.text()?),
+            }),
+            Self::HtmlTable(table) => Ok(Content::Text {
+                extension: Some(table.format.extension()),
+                text: table.apply(input.text()?)?,
             }),
-            Self::HtmlTextify => Ok(Content {
+            Self::HtmlTextify => Ok(Content::Text {
                 extension: Some("txt"),
-                text: html_text::textify(&input.text)?,
+                text: html_text::textify(input.text()?)?,
             }),
-            Self::HtmlUrlCanonicalize => Ok(Content {
+            Self::HtmlUrlCanonicalize => Ok(Content::Text {
                 extension: Some("html"),
-                text: html_url::canonicalize(url, &input.text)?,
+                text: html_url::canonicalize(url, input.text()?)?,
             }),
-            Self::JsonPrettify => Ok(Content {
+            Self::JsonPrettify => Ok(Content::Text {
                 extension: Some("json"),
-                text: json_prettify::prettify(&input.text)?,
+                text: json_prettify::prettify(input.text()?)?,
             }),
-            Self::JsonSimpleSelect(selector) => Ok(Content {
+            Self::JsonQuery(query) => Ok(Content::Text {
                 extension: Some("json"),
-                text: json_simple_select::apply(&input.text, selector)?,
+                text: query.apply(input.text()?)?,
             }),
-            Self::RegexReplace(rr) => Ok(Content {
-                extension: input.extension,
-                text: rr.replace_all(&input.text).to_string(),
+            Self::JsonRemoveKeys(pattern) => Ok(Content::Text {
+                extension: Some("json"),
+                text: json_remove::remove_keys(pattern, input.text()?)?,
+            }),
+            Self::JsonSimpleSelect(selector) => Ok(Content::Text {
+                extension: Some("json"),
+                text: json_simple_select::apply(input.text()?, selector)?,
+            }),
+            Self::JsonSortArrays(sort) => Ok(Content::Text {
+                extension: Some("json"),
+                text: sort.apply(input.text()?)?,
+            }),
+            Self::JsonSortKeys => Ok(Content::Text {
+                extension: Some("json"),
+                text: json_sort::sort_keys(input.text()?)?,
+            }),
+            Self::LinesDrop(pattern) => Ok(Content::Text {
+                extension: input.extension(),
+                text: lines::filter(pattern, false, input.text()?),
+            }),
+            Self::LinesHead(amount) => Ok(Content::Text {
+                extension: input.extension(),
+                text: lines::head(*amount, input.text()?),
+            }),
+            Self::LinesKeep(pattern) => Ok(Content::Text {
+                extension: input.extension(),
+                text: lines::filter(pattern, true, input.text()?),
+            }),
+            Self::LinesSort => Ok(Content::Text {
+                extension: input.extension(),
+                text: lines::sort(input.text()?),
             }),
-            Self::Rss(rss) => Ok(Content {
+            Self::LinesTail(amount) => Ok(Content::Text {
+                extension: input.extension(),
+                text: lines::tail(*amount, input.text()?),
+            }),
+            Self::LinesUnique => Ok(Content::Text {
+                extension: input.extension(),
+                text: lines::unique(input.text()?),
+            }),
+            Self::PdfTextify => match input {
+                Content::Binary { bytes, .. } => Ok(Content::Text {
+                    extension: Some("txt"),
+                    text: pdf_text::textify(&bytes)?,
+                }),
+                Content::Text { .. } => anyhow::bail!("pdf_textify requires binary PDF content"),
+            },
+            Self::RegexReplace(rr) => Ok(Content::Text {
+                extension: input.extension(),
+                text: rr.replace_all(input.text()?).to_string(),
+            }),
+            Self::Rss(rss) => Ok(Content::Text {
                 extension: Some("xml"),
-                text: rss.generate(url, &input.text)?,
+                text: rss.generate(url, input.text()?)?,
             }),
+            Self::Script(script) => script.apply(url, &input),
+            Self::XmlPrettify => Ok(Content::Text {
+                extension: Some("xml"),
+                text: xml_pretty::prettify(input.text()?)?,
+            }),
+            Self::XmlSelect(selector) => Ok(Content::Text {
+                extension: Some("xml"),
+                text: xml_selector::apply(selector, input.text()?)?,
+            }),
+            Self::XpathRemove(xpath) => {
+                let text = input.text()?;
+                let is_xml = xml::is_xml(input.extension(), text);
+                Ok(Content::Text {
+                    extension: Some(if is_xml { "xml" } else { "html" }),
+                    text: xpath.remove(text, is_xml)?,
+                })
+            }
+            Self::XpathSelect(xpath) => {
+                let text = input.text()?;
+                let is_xml = xml::is_xml(input.extension(), text);
+                Ok(Content::Text {
+                    extension: Some(if is_xml { "xml" } else { "html" }),
+                    text: xpath.select(text, is_xml)?,
+                })
+            }
         }
     }
 
@@ -145,3 +338,29 @@ impl Editor {
         Ok(content)
     }
 }
+
+#[test]
+#[should_panic = "the content is binary (pdf) and can not be edited as text"]
+fn text_editor_fails_on_binary() {
+    let url = Url::parse("https://edjopato.de/").unwrap();
+    let content = Content::Binary {
+        extension: Some("pdf"),
+        bytes: vec![0x25, 0x50, 0x44, 0x46, 0xff],
+    };
+    Editor::apply_many(&[Editor::HtmlTextify], &url, content).unwrap();
+}
+
+#[test]
+#[should_panic = "in editor[1] exec"]
+fn exec_failure_has_editor_context() {
+    let url = Url::parse("https://edjopato.de/").unwrap();
+    let content = Content::Text {
+        extension: Some("html"),
+        text: "<p>Hello</p>".to_owned(),
+    };
+    let editors = serde_json::from_str::<Vec<Editor>>(
+        r#"["html_textify", {"exec": {"command": "sh", "args": ["-c", "exit 1"]}}]"#,
+    )
+    .unwrap();
+    Editor::apply_many(&editors, &url, content).unwrap();
+}
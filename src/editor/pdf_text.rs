@@ -0,0 +1,90 @@
+use core::fmt::Write as _;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use anyhow::Context as _;
+use lazy_regex::regex;
+
+pub fn textify(pdf: &[u8]) -> anyhow::Result<String> {
+    // The extraction panics on some malformed PDFs
+    let pages = catch_unwind(AssertUnwindSafe(|| {
+        pdf_extract::extract_text_from_mem_by_pages(pdf)
+    }))
+    .map_err(|_| anyhow::anyhow!("the PDF extraction panicked"))
+    .and_then(|result| Ok(result?))
+    .context("failed to extract the text of the PDF")?;
+
+    let mut result = String::new();
+    for (index, page) in pages.iter().enumerate() {
+        let page = page
+            .lines()
+            .map(str::trim_end)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let page = regex!(r"\n{3,}").replace_all(page.trim(), "\n\n");
+        _ = write!(result, "--- Page {} ---\n{page}\n\n", index + 1);
+    }
+    Ok(result.trim_end().to_owned())
+}
+
+/// Create a PDF with one page per given text
+#[cfg(test)]
+fn create_pdf(pages: &[&str]) -> Vec<u8> {
+    let font = 3 + pages.len() * 2;
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_owned(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            (0..pages.len())
+                .map(|index| format!("{} 0 R", 3 + index * 2))
+                .collect::<Vec<_>>()
+                .join(" "),
+            pages.len()
+        ),
+    ];
+    for (index, text) in pages.iter().enumerate() {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 612 792] /Contents {} 0 R /Resources << /Font << /F1 {font} 0 R >> >> >>",
+            4 + index * 2
+        ));
+        let stream = format!("BT /F1 24 Tf 72 700 Td ({text}) Tj ET");
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{stream}\nendstream",
+            stream.len()
+        ));
+    }
+    objects.push("<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica >>".to_owned());
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::new();
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        _ = write!(pdf, "{} 0 obj\n{object}\nendobj\n", index + 1);
+    }
+    let xref = pdf.len();
+    _ = write!(pdf, "xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1);
+    for offset in offsets {
+        _ = writeln!(pdf, "{offset:010} 00000 n ");
+    }
+    _ = write!(
+        pdf,
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+        objects.len() + 1
+    );
+    pdf.into_bytes()
+}
+
+#[test]
+fn pages_are_separated() {
+    let pdf = create_pdf(&["Hello World", "Second page"]);
+    let text = textify(&pdf).unwrap();
+    assert_eq!(
+        text,
+        "--- Page 1 ---\nHello World\n\n--- Page 2 ---\nSecond page"
+    );
+}
+
+#[test]
+#[should_panic = "failed to extract the text of the PDF"]
+fn no_pdf_fails() {
+    textify(b"<html></html>").unwrap();
+}
@@ -19,7 +19,7 @@ impl RegexReplacer {
     }
 }
 
-fn deserialize_regex<'de, D>(deserializer: D) -> Result<Regex, D::Error>
+pub fn deserialize_regex<'de, D>(deserializer: D) -> Result<Regex, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -118,12 +118,12 @@ impl Rss {
                 builder.pub_date(bla.to_rfc2822());
             }
 
-            let content = super::Content {
+            let content = super::Content::Text {
                 extension: Some("html"),
                 text: item.html(),
             };
             let content = Editor::apply_many(&self.content_editors, url, content)?;
-            builder.content(content.text);
+            builder.content(content.into_text()?);
 
             items.push(builder.build());
         }
@@ -0,0 +1,138 @@
+use rhai::{Dynamic, Engine, Scope};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use url::Url;
+
+use super::Content;
+use super::exec::Extension;
+
+/// Scripts are stopped after this amount of operations so they can not hang the run
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Script {
+    /// [Rhai](https://rhai.rs/) script returning the new text
+    pub code: Code,
+
+    /// File extension of the output. Defaults to the extension of the input.
+    #[serde(default)]
+    pub extension: Option<Extension>,
+}
+
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(try_from = "String")]
+pub struct Code(#[schemars(with = "String")] String);
+
+impl TryFrom<String> for Code {
+    type Error = String;
+
+    fn try_from(code: String) -> Result<Self, Self::Error> {
+        engine()
+            .compile(&code)
+            .map_err(|err| format!("script is invalid: {err}"))?;
+        Ok(Self(code))
+    }
+}
+
+/// Sandboxed engine without access to files or the network and with limits on the resources
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.disable_symbol("eval");
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(100 * 1024 * 1024);
+    engine.set_max_array_size(1_000_000);
+    engine.set_max_map_size(100_000);
+    engine
+}
+
+impl Script {
+    /// Run the script with the variables `text`, `url` and `extension`
+    pub fn apply(&self, url: &Url, input: &Content) -> anyhow::Result<Content> {
+        let mut scope = Scope::new();
+        scope.push("text", input.text()?.to_owned());
+        scope.push("url", url.to_string());
+        scope.push(
+            "extension",
+            input.extension().map_or(Dynamic::UNIT, Dynamic::from),
+        );
+
+        let result = engine()
+            .eval_with_scope::<Dynamic>(&mut scope, &self.code.0)
+            .map_err(|err| anyhow::anyhow!("script failed: {err}"))?;
+        let text = result
+            .into_string()
+            .map_err(|kind| anyhow::anyhow!("script should return a string but returned {kind}"))?;
+
+        Ok(Content::Text {
+            extension: self
+                .extension
+                .map_or_else(|| input.extension(), |extension| Some(extension.as_str())),
+            text,
+        })
+    }
+}
+
+#[cfg(test)]
+#[track_caller]
+fn case(code: &str, input: &str) -> anyhow::Result<String> {
+    let script = Script {
+        code: Code::try_from(code.to_owned()).unwrap(),
+        extension: None,
+    };
+    let url = Url::parse("https://edjopato.de/post/").unwrap();
+    let content = Content::Text {
+        extension: Some("txt"),
+        text: input.to_owned(),
+    };
+    script.apply(&url, &content)?.into_text()
+}
+
+#[test]
+fn replaces() {
+    let result = case(
+        r#"text.replace("2026-10-17", "today"); text"#,
+        "Updated 2026-10-17",
+    )
+    .unwrap();
+    assert_eq!(result, "Updated today");
+}
+
+#[test]
+fn variables() {
+    let result = case(r"`${url} ${extension} ${text.len()}`", "Hello").unwrap();
+    assert_eq!(result, "https://edjopato.de/post/ txt 5");
+}
+
+#[test]
+fn computes_total() {
+    let code = r#"
+        let total = 0;
+        for line in text.split("\n") {
+            total += parse_int(line);
+        }
+        text + "\ntotal: " + total
+    "#;
+    let result = case(code, "1\n2\n3").unwrap();
+    assert_eq!(result, "1\n2\n3\ntotal: 6");
+}
+
+#[test]
+#[should_panic = "Too many operations"]
+fn endless_loop_is_stopped() {
+    case("loop {}", "").unwrap();
+}
+
+#[test]
+#[should_panic = "script should return a string but returned i64"]
+fn returns_no_string() {
+    case("42", "").unwrap();
+}
+
+#[test]
+#[should_panic = "script is invalid"]
+fn invalid_script() {
+    serde_json::from_str::<Script>(r#"{"code": "let = ;"}"#).unwrap();
+}
@@ -0,0 +1,66 @@
+use html5ever::serialize::TraversalScope;
+use scraper::{ElementRef, Html, HtmlTreeSink};
+use xml5ever::driver::{XmlParseOpts, parse_document};
+use xml5ever::serialize::{SerializeOpts, serialize};
+use xml5ever::tendril::TendrilSink as _;
+
+/// Parse XML into the same tree structure as HTML in order to reuse the HTML tooling
+pub fn parse(xml: &str) -> Html {
+    parse_document(
+        HtmlTreeSink::new(Html::new_document()),
+        XmlParseOpts::default(),
+    )
+    .one(xml)
+}
+
+/// Whether the content is likely XML instead of HTML
+pub fn is_xml(extension: Option<&str>, text: &str) -> bool {
+    matches!(extension, Some("xml" | "rss" | "atom" | "svg"))
+        || text.trim_start().starts_with("<?xml")
+}
+
+/// The XML declaration is not part of the parsed tree and always added as the output is UTF-8
+pub fn serialize_document(xml: &Html) -> anyhow::Result<String> {
+    let mut buf = br#"<?xml version="1.0" encoding="UTF-8"?>"#.to_vec();
+    let opts = SerializeOpts {
+        traversal_scope: TraversalScope::ChildrenOnly(None),
+    };
+    serialize(&mut buf, xml, opts)?;
+    Ok(String::from_utf8(buf)?)
+}
+
+pub fn serialize_element(element: &ElementRef) -> anyhow::Result<String> {
+    let mut buf = Vec::new();
+    let opts = SerializeOpts {
+        traversal_scope: TraversalScope::IncludeNode,
+    };
+    serialize(&mut buf, element, opts)?;
+    Ok(String::from_utf8(buf)?)
+}
+
+#[test]
+fn roundtrip() {
+    let xml = r#"<?xml version="1.0" encoding="UTF-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"><url><loc>https://edjopato.de/</loc></url><url><loc>https://edjopato.de/post/</loc><empty/></url></urlset>"#;
+    let parsed = parse(xml);
+    assert_eq!(
+        serialize_document(&parsed).unwrap(),
+        xml.replace("<empty/>", "<empty></empty>")
+    );
+}
+
+#[test]
+fn element() {
+    let parsed = parse("<root><Item Key=\"A\">a</Item></root>");
+    let element = parsed.root_element().child_elements().next().unwrap();
+    assert_eq!(
+        serialize_element(&element).unwrap(),
+        "<Item Key=\"A\">a</Item>"
+    );
+}
+
+#[test]
+fn detects_xml() {
+    assert!(is_xml(Some("xml"), "<root/>"));
+    assert!(is_xml(None, "  <?xml version=\"1.0\"?><root/>"));
+    assert!(!is_xml(Some("html"), "<html></html>"));
+}
@@ -0,0 +1,123 @@
+use ego_tree::NodeId;
+use scraper::node::Text;
+use scraper::{Html, Node, StrTendril};
+
+use super::xml;
+
+/// Indent the XML with tabs and sort the attributes.
+/// Elements only containing text are kept on one line.
+pub fn prettify(input: &str) -> anyhow::Result<String> {
+    let mut document = xml::parse(input);
+
+    let elements = document
+        .tree
+        .root()
+        .descendants()
+        .filter(|node| node.value().is_element())
+        .map(|node| (node.id(), node.ancestors().count()))
+        .collect::<Vec<_>>();
+    for (id, depth) in elements {
+        let mut node = document.tree.get_mut(id).expect("element exists");
+        if let Node::Element(element) = node.value() {
+            element.attrs.sort_keys();
+        }
+        indent_children(&mut document, id, depth);
+    }
+    let root = document.tree.root().id();
+    indent_children(&mut document, root, 0);
+
+    let pretty = xml::serialize_document(&document)?;
+    Ok(pretty
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n"))
+}
+
+/// Put every child on its own line when there is more than text
+fn indent_children(document: &mut Html, id: NodeId, depth: usize) {
+    let node = document.tree.get(id).expect("node exists");
+    let children = node.children().map(|child| child.id()).collect::<Vec<_>>();
+    let only_text = node.children().all(|child| child.value().is_text());
+
+    for child in &children {
+        let mut child = document.tree.get_mut(*child).expect("child exists");
+        if let Node::Text(text) = child.value() {
+            let trimmed = text.trim();
+            if trimmed.is_empty() && !only_text {
+                child.detach();
+            } else {
+                text.text = StrTendril::from(trimmed);
+            }
+        }
+    }
+    if only_text {
+        return;
+    }
+
+    let indent = |depth: usize| {
+        Node::Text(Text {
+            text: StrTendril::from(format!("\n{}", "\t".repeat(depth))),
+        })
+    };
+    let remaining = document
+        .tree
+        .get(id)
+        .expect("node exists")
+        .children()
+        .map(|child| child.id())
+        .collect::<Vec<_>>();
+    for child in remaining {
+        document
+            .tree
+            .get_mut(child)
+            .expect("child exists")
+            .insert_before(indent(depth));
+    }
+    document
+        .tree
+        .get_mut(id)
+        .expect("node exists")
+        .append(indent(depth.saturating_sub(1)));
+}
+
+#[test]
+fn sitemap() {
+    let ugly = r#"<?xml version="1.0" encoding="UTF-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"><url><loc>https://edjopato.de/</loc><lastmod>2026-01-01</lastmod></url><url>
+    <loc>  https://edjopato.de/post/  </loc></url></urlset>"#;
+    assert_eq!(
+        prettify(ugly).unwrap(),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">
+	<url>
+		<loc>https://edjopato.de/</loc>
+		<lastmod>2026-01-01</lastmod>
+	</url>
+	<url>
+		<loc>https://edjopato.de/post/</loc>
+	</url>
+</urlset>"#
+    );
+}
+
+#[test]
+fn attributes_sorted() {
+    let ugly = r#"<feed><link rel="self" href="https://edjopato.de/feed.xml"/></feed>"#;
+    assert_eq!(
+        prettify(ugly).unwrap(),
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed>
+	<link href="https://edjopato.de/feed.xml" rel="self"></link>
+</feed>"#
+    );
+}
+
+#[test]
+fn mixed_content() {
+    let ugly = "<p>Hello <b>World</b>!</p>";
+    assert_eq!(
+        prettify(ugly).unwrap(),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<p>\n\tHello\n\t<b>World</b>\n\t!\n</p>"
+    );
+}
@@ -0,0 +1,55 @@
+use scraper::Selector;
+
+use super::xml;
+
+pub fn apply(selector: &Selector, input: &str) -> anyhow::Result<String> {
+    let parsed = xml::parse(input);
+    let selected = parsed
+        .select(selector)
+        .map(|element| xml::serialize_element(&element))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    anyhow::ensure!(!selected.is_empty(), "selected nothing");
+    Ok(selected.join("\n"))
+}
+
+#[cfg(test)]
+const EXAMPLE_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?><feed xmlns="http://www.w3.org/2005/Atom"><title>Blog</title><updated>2026-10-17T12:00:00Z</updated><entry><title>First</title><link href="https://edjopato.de/post/first/"/></entry><entry><title>Second</title></entry></feed>"#;
+
+#[test]
+fn selects_case_sensitive() {
+    let selector = Selector::parse("feed > title").unwrap();
+    let xml = apply(
+        &selector,
+        "<feed><title>Blog</title><Title>Other</Title></feed>",
+    )
+    .unwrap();
+    assert_eq!(xml, "<title>Blog</title>");
+}
+
+#[test]
+fn selects_multiple() {
+    let selector = Selector::parse("entry > title").unwrap();
+    let xml = apply(&selector, EXAMPLE_XML).unwrap();
+    assert_eq!(
+        xml,
+        r#"<title xmlns="http://www.w3.org/2005/Atom">First</title>
+<title xmlns="http://www.w3.org/2005/Atom">Second</title>"#
+    );
+}
+
+#[test]
+fn selects_by_attribute() {
+    let selector = Selector::parse("entry:has(link[href])").unwrap();
+    let xml = apply(&selector, EXAMPLE_XML).unwrap();
+    assert_eq!(
+        xml,
+        r#"<entry xmlns="http://www.w3.org/2005/Atom"><title>First</title><link href="https://edjopato.de/post/first/"></link></entry>"#
+    );
+}
+
+#[test]
+#[should_panic = "selected nothing"]
+fn select_not_found() {
+    let selector = Selector::parse("item").unwrap();
+    apply(&selector, EXAMPLE_XML).unwrap();
+}
@@ -0,0 +1,319 @@
+#![expect(
+    clippy::mutable_key_type,
+    reason = "sxd nodes are hashed by their identity which does not change"
+)]
+
+use std::collections::HashMap;
+
+use ego_tree::{NodeId, NodeRef};
+use html5ever::QualName;
+use schemars::JsonSchema;
+use scraper::{ElementRef, Html, Node};
+use serde::Deserialize;
+use sxd_document::Package;
+use sxd_document::dom::{Document, Element};
+use sxd_xpath::nodeset::Node as XNode;
+use sxd_xpath::{Context, Factory, Value};
+
+use super::xml;
+
+/// An [XPath 1.0](https://developer.mozilla.org/en-US/docs/Web/XML/XPath) expression.
+/// It is checked to be valid when the config is loaded.
+#[derive(Debug, Clone, Deserialize, JsonSchema)]
+#[serde(try_from = "String")]
+pub struct XPath(#[schemars(with = "String")] String);
+
+impl TryFrom<String> for XPath {
+    type Error = String;
+
+    fn try_from(xpath: String) -> Result<Self, Self::Error> {
+        build(&xpath)?;
+        Ok(Self(xpath))
+    }
+}
+
+fn build(xpath: &str) -> Result<sxd_xpath::XPath, String> {
+    Factory::new()
+        .build(xpath)
+        .map_err(|err| format!("xpath {xpath:?} is invalid: {err}"))?
+        .ok_or_else(|| format!("xpath {xpath:?} is empty"))
+}
+
+/// Part of the parsed document which was selected by the xpath
+enum Target {
+    Node(NodeId),
+    Attribute(NodeId, QualName),
+}
+
+enum Selected {
+    Targets(Vec<Target>),
+    /// Result of expressions like `count(//p)` which do not select nodes
+    Value(String),
+}
+
+impl XPath {
+    pub fn select(&self, input: &str, is_xml: bool) -> anyhow::Result<String> {
+        let document = parse(input, is_xml);
+        let targets = match self.evaluate(&document)? {
+            Selected::Targets(targets) => targets,
+            Selected::Value(value) => return Ok(value),
+        };
+        anyhow::ensure!(!targets.is_empty(), "selected nothing");
+        let mut selected = Vec::new();
+        for target in targets {
+            let text = match target {
+                Target::Node(id) => {
+                    let node = document.tree.get(id).expect("selected node should exist");
+                    match node.value() {
+                        Node::Document => serialize_document(&document, is_xml)?,
+                        Node::Element(_) => {
+                            let element = ElementRef::wrap(node).expect("node is an element");
+                            if is_xml {
+                                xml::serialize_element(&element)?
+                            } else {
+                                element.html()
+                            }
+                        }
+                        Node::Text(text) => text.to_string(),
+                        Node::Comment(comment) => comment.to_string(),
+                        Node::ProcessingInstruction(instruction) => instruction.data.to_string(),
+                        Node::Fragment | Node::Doctype(_) => continue,
+                    }
+                }
+                Target::Attribute(id, name) => document
+                    .tree
+                    .get(id)
+                    .and_then(|node| node.value().as_element())
+                    .and_then(|element| element.attrs.get(&name))
+                    .map(ToString::to_string)
+                    .unwrap_or_default(),
+            };
+            selected.push(text);
+        }
+        Ok(selected.join("\n"))
+    }
+
+    pub fn remove(&self, input: &str, is_xml: bool) -> anyhow::Result<String> {
+        let mut document = parse(input, is_xml);
+        let Selected::Targets(targets) = self.evaluate(&document)? else {
+            anyhow::bail!("xpath does not select nodes which could be removed");
+        };
+        let root = document.tree.root().id();
+        for target in targets {
+            match target {
+                Target::Node(id) => {
+                    anyhow::ensure!(id != root, "the whole document can not be removed");
+                    if let Some(mut node) = document.tree.get_mut(id) {
+                        node.detach();
+                    }
+                }
+                Target::Attribute(id, name) => {
+                    if let Some(mut node) = document.tree.get_mut(id)
+                        && let Node::Element(element) = node.value()
+                    {
+                        element.attrs.shift_remove(&name);
+                    }
+                }
+            }
+        }
+        serialize_document(&document, is_xml)
+    }
+
+    fn evaluate(&self, document: &Html) -> anyhow::Result<Selected> {
+        let xpath = build(&self.0).map_err(anyhow::Error::msg)?;
+        let package = Package::new();
+        let converted = package.as_document();
+        let mut targets = HashMap::new();
+        convert(converted, document, &mut targets);
+        let value = xpath
+            .evaluate(&Context::new(), converted.root())
+            .map_err(|err| anyhow::anyhow!("xpath failed: {err}"))?;
+        Ok(match value {
+            Value::Nodeset(nodes) => Selected::Targets(
+                nodes
+                    .document_order()
+                    .iter()
+                    .filter_map(|node| targets.remove(node))
+                    .collect(),
+            ),
+            other => Selected::Value(other.string()),
+        })
+    }
+}
+
+fn parse(input: &str, is_xml: bool) -> Html {
+    if is_xml {
+        xml::parse(input)
+    } else {
+        Html::parse_document(input)
+    }
+}
+
+fn serialize_document(document: &Html, is_xml: bool) -> anyhow::Result<String> {
+    if is_xml {
+        xml::serialize_document(document)
+    } else {
+        Ok(document.html())
+    }
+}
+
+/// Create a copy of the parsed document the xpath can be evaluated on.
+/// Namespaces are ignored so elements and attributes are matched by their local name.
+fn convert<'d>(converted: Document<'d>, document: &Html, targets: &mut HashMap<XNode<'d>, Target>) {
+    let root = converted.root();
+    targets.insert(XNode::Root(root), Target::Node(document.tree.root().id()));
+    for child in document.tree.root().children() {
+        match child.value() {
+            Node::Element(_) => root.append_child(convert_element(converted, child, targets)),
+            Node::Comment(comment) => {
+                let comment = converted.create_comment(comment);
+                root.append_child(comment);
+                targets.insert(XNode::Comment(comment), Target::Node(child.id()));
+            }
+            Node::ProcessingInstruction(instruction) => {
+                let instruction = converted
+                    .create_processing_instruction(&instruction.target, Some(&instruction.data));
+                root.append_child(instruction);
+                targets.insert(
+                    XNode::ProcessingInstruction(instruction),
+                    Target::Node(child.id()),
+                );
+            }
+            _ => {}
+        }
+    }
+}
+
+fn convert_element<'d>(
+    converted: Document<'d>,
+    node: NodeRef<Node>,
+    targets: &mut HashMap<XNode<'d>, Target>,
+) -> Element<'d> {
+    let Node::Element(element) = node.value() else {
+        unreachable!("only called with elements");
+    };
+    let result = converted.create_element(element.name.local.as_ref());
+    targets.insert(XNode::Element(result), Target::Node(node.id()));
+    for (name, value) in &element.attrs {
+        let attribute = result.set_attribute_value(name.local.as_ref(), value);
+        targets.insert(
+            XNode::Attribute(attribute),
+            Target::Attribute(node.id(), name.clone()),
+        );
+    }
+    for child in node.children() {
+        match child.value() {
+            Node::Element(_) => result.append_child(convert_element(converted, child, targets)),
+            Node::Text(text) => {
+                let text = converted.create_text(text);
+                result.append_child(text);
+                targets.insert(XNode::Text(text), Target::Node(child.id()));
+            }
+            Node::Comment(comment) => {
+                let comment = converted.create_comment(comment);
+                result.append_child(comment);
+                targets.insert(XNode::Comment(comment), Target::Node(child.id()));
+            }
+            Node::ProcessingInstruction(instruction) => {
+                let instruction = converted
+                    .create_processing_instruction(&instruction.target, Some(&instruction.data));
+                result.append_child(instruction);
+                targets.insert(
+                    XNode::ProcessingInstruction(instruction),
+                    Target::Node(child.id()),
+                );
+            }
+            _ => {}
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+const EXAMPLE_HTML: &str =
+    r#"<html><head></head><body><h2>News</h2><p class="a">A</p><h2>Old</h2><p>B</p></body></html>"#;
+
+#[cfg(test)]
+const EXAMPLE_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"><url><loc>https://edjopato.de/</loc><lastmod>2026-01-01</lastmod></url><url><loc>https://edjopato.de/post/</loc><lastmod>2026-02-01</lastmod></url></urlset>"#;
+
+#[cfg(test)]
+fn xpath(xpath: &str) -> XPath {
+    XPath::try_from(xpath.to_owned()).unwrap()
+}
+
+#[test]
+fn select_by_sibling_heading() {
+    let result = xpath("//h2[text()='News']/following-sibling::p[1]")
+        .select(EXAMPLE_HTML, false)
+        .unwrap();
+    assert_eq!(result, r#"<p class="a">A</p>"#);
+}
+
+#[test]
+fn select_by_text_content() {
+    let result = xpath("//p[contains(., 'B')]")
+        .select(EXAMPLE_HTML, false)
+        .unwrap();
+    assert_eq!(result, "<p>B</p>");
+}
+
+#[test]
+fn select_attribute_and_text() {
+    let result = xpath("//p/@class | //h2/text()")
+        .select(EXAMPLE_HTML, false)
+        .unwrap();
+    assert_eq!(result, "News\na\nOld");
+}
+
+#[test]
+fn select_value() {
+    let result = xpath("count(//p)").select(EXAMPLE_HTML, false).unwrap();
+    assert_eq!(result, "2");
+}
+
+#[test]
+#[should_panic = "selected nothing"]
+fn select_nothing() {
+    xpath("//span").select(EXAMPLE_HTML, false).unwrap();
+}
+
+#[test]
+fn select_xml() {
+    let result = xpath("//url[1]/loc").select(EXAMPLE_XML, true).unwrap();
+    assert_eq!(
+        result,
+        r#"<loc xmlns="http://www.sitemaps.org/schemas/sitemap/0.9">https://edjopato.de/</loc>"#
+    );
+}
+
+#[test]
+fn remove_html() {
+    let result = xpath("//h2[text()='Old'] | //p/@class")
+        .remove(EXAMPLE_HTML, false)
+        .unwrap();
+    assert_eq!(
+        result,
+        "<html><head></head><body><h2>News</h2><p>A</p><p>B</p></body></html>"
+    );
+}
+
+#[test]
+fn remove_xml() {
+    let result = xpath("//lastmod").remove(EXAMPLE_XML, true).unwrap();
+    assert_eq!(
+        result,
+        r#"<?xml version="1.0" encoding="UTF-8"?><urlset xmlns="http://www.sitemaps.org/schemas/sitemap/0.9"><url><loc>https://edjopato.de/</loc></url><url><loc>https://edjopato.de/post/</loc></url></urlset>"#
+    );
+}
+
+#[test]
+#[should_panic = "the whole document can not be removed"]
+fn remove_root() {
+    xpath("/").remove(EXAMPLE_HTML, false).unwrap();
+}
+
+#[test]
+#[should_panic = "is invalid"]
+fn invalid_xpath() {
+    XPath::try_from("//p[".to_owned()).unwrap();
+}
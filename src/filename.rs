@@ -30,6 +30,19 @@ pub fn filename(url: &Url) -> String {
     }
 }
 
+/// Short hash to distinguish otherwise identical filenames.
+///
+/// As its part of the filename it has to be stable between versions.
+/// Therefore, this is a simple FNV-1a instead of the std hasher.
+pub fn hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in content.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    format!("{hash:016x}")
+}
+
 /// Ensure all characters are alphanumeric
 ///
 /// Replaces non alphanumeric characters with -
@@ -47,6 +60,12 @@ fn alphanum_works() {
     assert_eq!(alphanum(":te_st:"), "te-st");
 }
 
+#[test]
+fn hash_is_stable() {
+    assert_eq!(hash(""), "cbf29ce484222325");
+    assert_eq!(hash("a"), "af63dc4c8601ec8c");
+}
+
 #[cfg(test)]
 /// test base name
 fn tb(url: &str) -> String {
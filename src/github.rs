@@ -5,21 +5,37 @@
 //! - <https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions>
 
 use std::env;
-use std::sync::LazyLock;
+use std::path::Path;
+use std::sync::{LazyLock, OnceLock};
 
 pub static IS_RUN_AS_GITHUB_ACTION: LazyLock<bool> =
     LazyLock::new(|| env::var_os("GITHUB_ACTIONS").is_some());
 
+/// The config file the annotations refer to
+static CONFIG_FILE: OnceLock<String> = OnceLock::new();
+
+pub fn set_config_file(path: &Path) {
+    _ = CONFIG_FILE.set(path.display().to_string());
+}
+
+fn annotate(command: &str, message: &str) {
+    if let Some(file) = CONFIG_FILE.get() {
+        println!("::{command} file={file}::{message}");
+    } else {
+        println!("::{command}::{message}");
+    }
+}
+
 pub fn error(message: &str) {
-    println!("::error file=website-stalker.yaml::{message}");
+    annotate("error", message);
 }
 
 pub fn warning(message: &str) {
-    println!("::warning file=website-stalker.yaml::{message}");
+    annotate("warning", message);
 }
 
 pub fn notice(message: &str) {
-    println!("::notice file=website-stalker.yaml::{message}");
+    annotate("notice", message);
 }
 
 /// See [`crate::notification`]
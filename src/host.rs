@@ -0,0 +1,78 @@
+use core::time::Duration;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+use crate::site::SiteDefaults;
+
+/// # Host
+/// Options for all sites on the given host.
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Host {
+    /// Wait time between requests to this host. Overrides the global `host_delay`.
+    #[serde(default, with = "humantime_serde")]
+    #[schemars(with = "Option<String>")]
+    pub delay: Option<Duration>,
+
+    /// Random additional wait time up to this value. Overrides the global `host_jitter`.
+    #[serde(default, with = "humantime_serde")]
+    #[schemars(with = "Option<String>")]
+    pub jitter: Option<Duration>,
+
+    /// Options for the sites of this host. They take precedence over the global `defaults`.
+    #[serde(flatten)]
+    pub site: SiteDefaults,
+}
+
+/// Wait time between requests to the same host
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Delay {
+    pub delay: Duration,
+    pub jitter: Duration,
+}
+
+impl Delay {
+    pub const DEFAULT: Self = Self {
+        delay: Duration::from_secs(5),
+        jitter: Duration::ZERO,
+    };
+
+    /// The delay with a random amount of jitter added
+    pub fn sample(self) -> Duration {
+        if self.jitter.is_zero() {
+            return self.delay;
+        }
+        let jitter_millis = u64::try_from(self.jitter.as_millis()).unwrap_or(u64::MAX);
+        let jitter = Duration::from_millis(fastrand::u64(0..=jitter_millis));
+        self.delay.saturating_add(jitter)
+    }
+}
+
+impl core::fmt::Display for Delay {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.jitter.is_zero() {
+            write!(fmt, "{:?}", self.delay)
+        } else {
+            write!(fmt, "{:?} (+ up to {:?})", self.delay, self.jitter)
+        }
+    }
+}
+
+#[test]
+fn sample_without_jitter_is_exact() {
+    assert_eq!(Delay::DEFAULT.sample(), Duration::from_secs(5));
+}
+
+#[test]
+fn sample_with_jitter_is_in_range() {
+    let delay = Delay {
+        delay: Duration::from_secs(1),
+        jitter: Duration::from_secs(2),
+    };
+    for _ in 0..100 {
+        let sample = delay.sample();
+        assert!(sample >= Duration::from_secs(1));
+        assert!(sample <= Duration::from_secs(3));
+    }
+}
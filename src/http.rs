@@ -1,15 +1,22 @@
 use core::time::Duration;
+use std::collections::{BTreeMap, HashMap};
 use std::net::SocketAddr;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime};
 
 use anyhow::Context as _;
+use reqwest::cookie::Jar;
 use reqwest::header::{HeaderMap, HeaderValue};
-use reqwest::{ClientBuilder, header};
+use reqwest::{Client, ClientBuilder, StatusCode, header};
+use schemars::JsonSchema;
+use serde::Deserialize;
+use tokio::time::sleep;
 use url::Url;
 
 use crate::editor::Content;
+use crate::retry::Retry;
 
-const USER_AGENT: &str = concat!(
+pub const USER_AGENT: &str = concat!(
     env!("CARGO_PKG_NAME"),
     "/",
     env!("CARGO_PKG_VERSION"),
@@ -18,6 +25,9 @@ const USER_AGENT: &str = concat!(
 );
 
 pub struct ResponseMeta {
+    /// Amount of requests needed including retries
+    pub attempts: u8,
+    pub headers: HeaderMap,
     pub http_version: reqwest::Version,
     pub ip_version: IpVersion,
     pub took: Duration,
@@ -38,48 +48,310 @@ impl core::fmt::Display for IpVersion {
     }
 }
 
-/// HTTP GET Request
+/// HTTP request method
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    #[default]
+    Get,
+    Post,
+    Put,
+    Patch,
+}
+
+impl Method {
+    pub const fn is_get(self) -> bool {
+        matches!(self, Self::Get)
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Get => "GET",
+            Self::Post => "POST",
+            Self::Put => "PUT",
+            Self::Patch => "PATCH",
+        }
+    }
+
+    const fn to_reqwest(self) -> reqwest::Method {
+        match self {
+            Self::Get => reqwest::Method::GET,
+            Self::Post => reqwest::Method::POST,
+            Self::Put => reqwest::Method::PUT,
+            Self::Patch => reqwest::Method::PATCH,
+        }
+    }
+}
+
+/// HTTP request body
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+#[serde(deny_unknown_fields)]
+pub enum Body {
+    /// Sent as is. Set the `Content-Type` via headers when needed.
+    Raw(String),
+    /// Sent as `application/json`
+    Json(serde_json::Value),
+    /// Sent as `application/x-www-form-urlencoded`
+    Form(BTreeMap<String, String>),
+}
+
+impl Body {
+    const fn content_type(&self) -> Option<&'static str> {
+        match self {
+            Self::Raw(_) => None,
+            Self::Json(_) => Some("application/json"),
+            Self::Form(_) => Some("application/x-www-form-urlencoded"),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        match self {
+            Self::Raw(raw) => raw.clone(),
+            Self::Json(value) => value.to_string(),
+            Self::Form(form) => url::form_urlencoded::Serializer::new(String::new())
+                .extend_pairs(form)
+                .finish(),
+        }
+    }
+}
+
+/// Options which require their own [`Client`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ClientOptions {
+    pub accept_invalid_certs: bool,
+    pub http1_only: bool,
+}
+
+impl ClientOptions {
+    /// Cookies are stored in the client. Sessions therefore need their own client.
+    pub fn build(self, cookies: Option<Arc<Jar>>) -> reqwest::Result<Client> {
+        let mut builder = ClientBuilder::new()
+            .danger_accept_invalid_certs(self.accept_invalid_certs)
+            .timeout(Duration::from_secs(30))
+            .user_agent(HeaderValue::from_static(USER_AGENT));
+        if self.http1_only {
+            builder = builder.http1_only();
+        }
+        if let Some(cookies) = cookies {
+            builder = builder.cookie_provider(cookies);
+        }
+        builder.build()
+    }
+}
+
+/// Clients shared between all requests of a run.
+///
+/// Reusing them keeps connection pools and TLS sessions alive.
+/// There is one client for each distinct [`ClientOptions`].
+#[derive(Clone, Default)]
+pub struct Clients {
+    clients: Arc<Mutex<HashMap<ClientOptions, Client>>>,
+}
+
+impl Clients {
+    pub fn get(&self, options: ClientOptions) -> reqwest::Result<Client> {
+        let mut clients = self
+            .clients
+            .lock()
+            .expect("a thread holding the clients should not panic");
+        let client = if let Some(client) = clients.get(&options) {
+            client.clone()
+        } else {
+            let client = options.build(None)?;
+            clients.insert(options, client.clone());
+            client
+        };
+        drop(clients);
+        Ok(client)
+    }
+}
+
+/// HTTP Request
 ///
 /// FROM provides an email address for the target host to be contacted in case of problems.
 /// See [HTTP From header](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/From)
-pub async fn get(
+///
+/// Returns no content when the server responded with `304 Not Modified` to a conditional request.
+pub async fn request(
+    client: &Client,
+    method: Method,
     url: &str,
-    additional_headers: HeaderMap,
-    accept_invalid_certs: bool,
-    http1_only: bool,
-) -> reqwest::Result<(Content, ResponseMeta)> {
-    let mut builder = ClientBuilder::new()
-        .danger_accept_invalid_certs(accept_invalid_certs)
-        .timeout(Duration::from_secs(30))
-        .user_agent(HeaderValue::from_static(USER_AGENT));
-    if http1_only {
-        builder = builder.http1_only();
-    }
-    let request = builder.build()?.get(url).headers(additional_headers);
+    body: Option<&Body>,
+    mut additional_headers: HeaderMap,
+    retry: Retry,
+) -> anyhow::Result<(Option<Content>, ResponseMeta)> {
+    let body = body.map(|body| {
+        if let Some(content_type) = body.content_type() {
+            additional_headers
+                .entry(header::CONTENT_TYPE)
+                .or_insert(HeaderValue::from_static(content_type));
+        }
+        body.to_text()
+    });
+
+    let mut attempt: u8 = 1;
+    let (response, took) = loop {
+        let mut request = client
+            .request(method.to_reqwest(), url)
+            .headers(additional_headers.clone());
+        if let Some(body) = &body {
+            request = request.body(body.clone());
+        }
 
-    let start = Instant::now();
-    let response = request.send().await?.error_for_status()?;
-    let took = Instant::now().saturating_duration_since(start);
+        let start = Instant::now();
+        let result = request.send().await;
+        let took = Instant::now().saturating_duration_since(start);
 
-    let extension = response
+        let (err, retryable, retry_after) = match result {
+            Ok(response)
+                if response.status().is_client_error() || response.status().is_server_error() =>
+            {
+                let retryable = is_retryable_status(response.status());
+                let retry_after = retry_after(response.headers());
+                let err = response
+                    .error_for_status()
+                    .expect_err("status is an error status");
+                (err, retryable, retry_after)
+            }
+            Ok(response) => break (response, took),
+            Err(err) => {
+                let retryable = err.is_timeout() || err.is_connect() || err.is_request();
+                (err, retryable, None)
+            }
+        };
+        let err = err.without_url();
+        let delay = if retryable {
+            retry.delay(attempt, retry_after)
+        } else {
+            None
+        };
+        let Some(delay) = delay else {
+            if attempt > 1 {
+                return Err(
+                    anyhow::Error::new(err).context(format!("failed after {attempt} attempts"))
+                );
+            }
+            return Err(err.into());
+        };
+        sleep(delay).await;
+        attempt += 1;
+    };
+
+    let content_type = response
         .headers()
         .get(header::CONTENT_TYPE)
         .and_then(|value| value.to_str().ok())
-        .and_then(mime2ext::mime2ext);
+        .map(ToOwned::to_owned);
+    let extension = content_type.as_deref().and_then(mime2ext::mime2ext);
     let ip_version = match response.remote_addr() {
         Some(SocketAddr::V4(_)) => IpVersion::IPv4,
         Some(SocketAddr::V6(_)) => IpVersion::IPv6,
         None => IpVersion::None,
     };
     let meta = ResponseMeta {
+        attempts: attempt,
+        headers: response.headers().clone(),
         http_version: response.version(),
         ip_version,
         took,
         url: response.url().clone(),
     };
-    let text = response.text().await?;
-    let content = Content { extension, text };
-    Ok((content, meta))
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok((None, meta));
+    }
+    let content = read_content(response, content_type.as_deref(), extension)
+        .await
+        .map_err(reqwest::Error::without_url)?;
+    Ok((Some(content), meta))
+}
+
+async fn read_content(
+    response: reqwest::Response,
+    content_type: Option<&str>,
+    extension: Option<&'static str>,
+) -> reqwest::Result<Content> {
+    if content_type.is_some_and(is_text_mime) {
+        let text = response.text().await?;
+        return Ok(Content::Text { extension, text });
+    }
+    let bytes = response.bytes().await?.to_vec();
+    if content_type.is_some_and(is_binary_mime) {
+        return Ok(Content::Binary { extension, bytes });
+    }
+    // Unknown or generic types like application/octet-stream are text when valid UTF-8
+    Ok(match String::from_utf8(bytes) {
+        Ok(text) => Content::Text { extension, text },
+        Err(err) => Content::Binary {
+            extension,
+            bytes: err.into_bytes(),
+        },
+    })
+}
+
+/// Lowercase `type/subtype` of a `Content-Type`
+fn mime_essence(content_type: &str) -> Option<(String, String)> {
+    let essence = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+    let (kind, subtype) = essence.split_once('/')?;
+    Some((kind.to_owned(), subtype.to_owned()))
+}
+
+/// Whether the [MIME type](https://developer.mozilla.org/en-US/docs/Web/HTTP/Guides/MIME_types) of a `Content-Type` is text based
+fn is_text_mime(content_type: &str) -> bool {
+    let Some((kind, subtype)) = mime_essence(content_type) else {
+        return false;
+    };
+    kind == "text"
+        || matches!(
+            subtype.as_str(),
+            "json" | "xml" | "javascript" | "ecmascript" | "x-www-form-urlencoded"
+        )
+        || subtype.ends_with("+json")
+        || subtype.ends_with("+xml")
+}
+
+/// Whether the `Content-Type` is clearly binary even when the body happens to be valid UTF-8
+fn is_binary_mime(content_type: &str) -> bool {
+    let Some((kind, subtype)) = mime_essence(content_type) else {
+        return false;
+    };
+    matches!(kind.as_str(), "image" | "audio" | "video" | "font")
+        || matches!(
+            subtype.as_str(),
+            "pdf" | "zip" | "gzip" | "x-tar" | "x-7z-compressed" | "wasm" | "vnd.rar"
+        )
+        || subtype.ends_with("+zip")
+}
+
+/// Server errors which are likely temporary
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::REQUEST_TIMEOUT
+        || status == StatusCode::TOO_MANY_REQUESTS
+        || status == StatusCode::BAD_GATEWAY
+        || status == StatusCode::SERVICE_UNAVAILABLE
+        || status == StatusCode::GATEWAY_TIMEOUT
+        || status == StatusCode::INTERNAL_SERVER_ERROR
+}
+
+/// Parse the [Retry-After header](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Retry-After)
+/// which is either in seconds or an HTTP date.
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let date = u64::try_from(date.timestamp()).ok()?;
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    Some(Duration::from_secs(date.saturating_sub(now)))
 }
 
 pub fn validate_from(from: &str) -> anyhow::Result<()> {
@@ -92,6 +364,82 @@ pub fn validate_from(from: &str) -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn body_form_is_urlencoded() {
+    let body = Body::Form(BTreeMap::from([
+        ("q".to_owned(), "website stalker".to_owned()),
+        ("page".to_owned(), "1".to_owned()),
+    ]));
+    assert_eq!(body.to_text(), "page=1&q=website+stalker");
+}
+
+#[test]
+fn body_json_is_compact() {
+    let body = Body::Json(serde_json::json!({"query": "{ viewer { login } }"}));
+    assert_eq!(body.to_text(), r#"{"query":"{ viewer { login } }"}"#);
+}
+
+#[test]
+fn text_mime_types() {
+    assert!(is_text_mime("text/html; charset=utf-8"));
+    assert!(is_text_mime("application/json"));
+    assert!(is_text_mime("application/rss+xml"));
+    assert!(is_text_mime("Application/XML"));
+    assert!(!is_text_mime("application/pdf"));
+    assert!(!is_text_mime("image/png"));
+    assert!(!is_text_mime("application/octet-stream"));
+    assert!(!is_text_mime("garbage"));
+}
+
+#[test]
+fn binary_mime_types() {
+    assert!(is_binary_mime("application/pdf"));
+    assert!(is_binary_mime("image/png"));
+    assert!(is_binary_mime("font/woff2"));
+    assert!(is_binary_mime("application/epub+zip"));
+    assert!(!is_binary_mime("application/octet-stream"));
+    assert!(!is_binary_mime("application/x-ndjson"));
+    assert!(!is_binary_mime("application/yaml"));
+    assert!(!is_binary_mime("garbage"));
+}
+
+#[test]
+fn clients_are_shared() {
+    let clients = Clients::default();
+    let options = ClientOptions::default();
+    clients.get(options).unwrap();
+    clients.get(options).unwrap();
+    clients
+        .get(ClientOptions {
+            accept_invalid_certs: false,
+            http1_only: true,
+        })
+        .unwrap();
+    assert_eq!(clients.clients.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn retry_after_seconds() {
+    let mut headers = HeaderMap::new();
+    headers.insert(header::RETRY_AFTER, HeaderValue::from_static("120"));
+    assert_eq!(retry_after(&headers), Some(Duration::from_mins(2)));
+}
+
+#[test]
+fn retry_after_date_in_the_past() {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::RETRY_AFTER,
+        HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+    );
+    assert_eq!(retry_after(&headers), Some(Duration::ZERO));
+}
+
+#[test]
+fn retry_after_missing() {
+    assert_eq!(retry_after(&HeaderMap::new()), None);
+}
+
 #[test]
 fn from_is_email() {
     validate_from("foo@bar.de").unwrap();
@@ -1,4 +1,4 @@
-use crate::github;
+use crate::{github, secrets};
 
 pub fn error_exit(message: &str) -> ! {
     error(message);
@@ -7,8 +7,9 @@ pub fn error_exit(message: &str) -> ! {
 
 /// This is not the underlying error. Hint the previous error and exit.
 pub fn notice_exit(message: &str) -> ! {
+    let message = secrets::redact(message);
     if *github::IS_RUN_AS_GITHUB_ACTION {
-        github::notice(message);
+        github::notice(&message);
     } else {
         eprintln!("{message}");
     }
@@ -16,21 +17,24 @@ pub fn notice_exit(message: &str) -> ! {
 }
 
 pub fn error(message: &str) {
+    let message = secrets::redact(message);
     if *github::IS_RUN_AS_GITHUB_ACTION {
-        github::error(message);
+        github::error(&message);
     } else {
         eprintln!("ERROR: {message}");
     }
 }
 
 pub fn warn(message: &str) {
+    let message = secrets::redact(message);
     if *github::IS_RUN_AS_GITHUB_ACTION {
-        github::warning(message);
+        github::warning(&message);
     } else {
         eprintln!("WARN: {message}");
     }
 }
 
 pub fn info(message: &str) {
+    let message = secrets::redact(message);
     eprintln!("INFO: {message}");
 }
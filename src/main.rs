@@ -1,16 +1,24 @@
-use core::time::Duration;
+use core::fmt::Write as _;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
 use std::{fs, process};
 
 use clap::Parser as _;
 use regex::Regex;
 use reqwest::header::{FROM, HeaderValue};
+use tokio::sync::Semaphore;
 use tokio::sync::mpsc::channel;
 use tokio::time::sleep;
 
 use crate::cli::Cli;
 use crate::config::Config;
-use crate::site::Site;
+use crate::retry::Retry;
+use crate::robots::RobotsTxt;
+use crate::session::Sessions;
+use crate::site::{Selection, Site};
+use crate::state::{SiteState, State};
 
 mod cli;
 mod commit_message;
@@ -19,13 +27,17 @@ mod editor;
 mod filename;
 mod git;
 mod github;
+mod host;
 mod http;
 mod logger;
 mod notification;
+mod retry;
+mod robots;
+mod secrets;
+mod session;
 mod site;
 mod site_store;
-
-const WAIT_BETWEEN_SAME_HOST: Duration = Duration::from_secs(5);
+mod state;
 
 pub enum ChangeKind {
     Init,
@@ -65,7 +77,7 @@ async fn main() {
                 println!("Git repository initialized.");
             }
             let from = std::env::var("WEBSITE_STALKER_FROM").ok();
-            if Config::load(from).is_err() {
+            if Config::load(&Config::default_path(), from).is_err() {
                 fs::write("website-stalker.yaml", Config::EXAMPLE)
                     .expect("failed to write example configuration file");
                 println!("Example configuration file generated.");
@@ -78,11 +90,15 @@ async fn main() {
             );
             eprintln!("\nConfiguration...");
             let from = std::env::var("WEBSITE_STALKER_FROM").ok();
-            match Config::load(from) {
+            let config = std::env::var_os("WEBSITE_STALKER_CONFIG")
+                .map_or_else(Config::default_path, PathBuf::from);
+            match Config::load(&config, from) {
                 Ok(_) => eprintln!("ok"),
                 Err(err) => {
+                    let err = format!("{err:#}");
                     eprintln!(
-                        "not ok.\n\n{err:#}\n\nCheck https://github.com/EdJoPaTo/website-stalker for configuration details."
+                        "not ok.\n\n{}\n\nCheck https://github.com/EdJoPaTo/website-stalker for configuration details.",
+                        secrets::redact(&err)
                     );
                     process::exit(1);
                 }
@@ -91,19 +107,29 @@ async fn main() {
         Cli::Run {
             all: _all,
             commit: do_commit,
+            config,
             from,
             notification_commit_template,
             notifications,
+            tag,
+            exclude_tag,
             site_filter,
         } => {
-            let site_filter =
-                site_filter.map(|regex| Regex::new(&format!("(?i){}", regex.as_str())).unwrap());
+            let selection = Selection {
+                filter: site_filter
+                    .map(|regex| Regex::new(&format!("(?i){}", regex.as_str())).unwrap()),
+                tags: tag,
+                exclude_tags: exclude_tag,
+            };
+            let config = config.unwrap_or_else(Config::default_path);
+            github::set_config_file(&config);
             run(
+                &config,
                 do_commit,
                 from,
                 notifications,
                 notification_commit_template,
-                site_filter.as_ref(),
+                &selection,
             )
             .await;
         }
@@ -112,13 +138,16 @@ async fn main() {
 
 #[expect(clippy::too_many_lines)]
 async fn run(
+    config_path: &Path,
     do_commit: bool,
     from: Option<String>,
     notifications: pling::clap::Args,
     notification_commit_template: Option<String>,
-    site_filter: Option<&Regex>,
+    selection: &Selection,
 ) {
-    let config = Config::load(from).expect("failed to load your configuration");
+    let config = Config::load(config_path, from).unwrap_or_else(|err| {
+        logger::error_exit(&format!("failed to load your configuration: {err:#}"))
+    });
     let from = config
         .from
         .parse::<HeaderValue>()
@@ -128,12 +157,12 @@ async fn run(
     let sites_total = sites.len();
     let sites = sites
         .into_iter()
-        .filter(|site| site_filter.is_none_or(|filter| filter.is_match(site.url.as_str())))
+        .filter(|site| selection.is_selected(site))
         .collect::<Vec<_>>();
     let sites_amount = sites.len();
     if sites.is_empty() {
         logger::error_exit(
-            "The site-filter filtered everything out. Change the filter or use all sites with 'run --all'.",
+            "The site-filter and tags selected no site. Change them or use all sites with 'run --all'.",
         );
     }
 
@@ -159,48 +188,118 @@ async fn run(
         }
     }
 
+    let mut state = State::load().expect("failed to load the state file");
+
+    // Files of sites not selected by the site-filter or tags are not gone
     if sites_amount == sites_total {
         let paths = Site::get_all_file_paths(&sites);
-        let removed = site_store::remove_gone(&paths)
+        let keep = [
+            Some(config_path.to_path_buf()),
+            config::include_dir(config_path),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        let removed = site_store::remove_gone(&paths, &keep)
             .expect("Should be able to cleanup the superfluous files");
         for file in removed {
             logger::warn(&format!("Remove superfluous {}", file.display()));
         }
+        state.retain(&sites);
     }
 
     if sites_amount < sites_total {
         logger::info(&format!(
-            "Your configuration file contains {sites_total} sites of which {sites_amount} are selected by your filter."
+            "Your configuration file contains {sites_total} sites of which {sites_amount} are selected by your site-filter and tags."
         ));
     }
 
-    let mut groups: HashMap<String, Vec<Site>> = HashMap::new();
+    let run_start = SystemTime::now();
+    let (sites, not_due): (Vec<_>, Vec<_>) = sites
+        .into_iter()
+        .partition(|site| state.get(site).is_due(site, run_start));
+    let sites_amount = sites.len();
+    if !not_due.is_empty() {
+        logger::info(&format!(
+            "Skipped {} sites which are not due yet because of their interval.",
+            not_due.len()
+        ));
+    }
+
+    let mut groups: HashMap<String, Vec<(Site, SiteState)>> = HashMap::new();
     for site in sites {
         let host = site.url.host_str().unwrap().to_owned();
-        groups.entry(host).or_default().push(site);
+        let site_state = state.get(&site);
+        groups.entry(host).or_default().push((site, site_state));
     }
 
     let distinct_hosts = groups.len();
     println!("Begin stalking of {sites_amount} sites on {distinct_hosts} hosts...");
     if distinct_hosts < sites_amount {
         logger::info(&format!(
-            "Some sites are on the same host. There is a wait time of {WAIT_BETWEEN_SAME_HOST:?} between each request to the same host in order to reduce load on the server.",
+            "Some sites are on the same host. There is a wait time of {} between each request to the same host in order to reduce load on the server.",
+            config.default_host_delay(),
         ));
     }
 
+    let concurrency_limit = config
+        .max_concurrent_requests
+        .map(|max| Arc::new(Semaphore::new(max.get())));
+
+    let clients = http::Clients::default();
     let mut rx = {
         let (tx, rx) = channel(10);
         #[expect(clippy::iter_over_hash_type)]
-        for (_, sites) in groups {
+        for (host, sites) in groups {
             let from = from.clone();
+            let clients = clients.clone();
             let tx = tx.clone();
+            let mut delay = config.host_delay(&host);
+            let concurrency_limit = concurrency_limit.clone();
+            let respect_robots_txt = config.respect_robots_txt;
             tokio::spawn(async move {
-                for (i, site) in sites.into_iter().enumerate() {
-                    if i > 0 {
-                        sleep(WAIT_BETWEEN_SAME_HOST).await;
+                let mut sessions = Sessions::default();
+                let mut robots_txt = RobotsTxt::new(clients.clone());
+                let mut is_first_request = true;
+                for (site, site_state) in sites {
+                    if !is_first_request {
+                        sleep(delay.sample()).await;
+                    }
+                    is_first_request = false;
+                    // The robots.txt request counts against the limit too
+                    let permit = match &concurrency_limit {
+                        Some(semaphore) => Some(
+                            semaphore
+                                .acquire()
+                                .await
+                                .expect("concurrency limit is never closed"),
+                        ),
+                        None => None,
+                    };
+                    if respect_robots_txt && !site.options.ignore_robots_txt.unwrap_or(false) {
+                        // Cached per host so the crawl delay applies from the next request on
+                        if let Some(crawl_delay) = robots_txt
+                            .crawl_delay(&site.url, &from, site.client_options())
+                            .await
+                        {
+                            delay.delay = delay.delay.max(crawl_delay);
+                        }
+                        if !robots_txt
+                            .is_allowed(&site.url, &from, site.client_options())
+                            .await
+                        {
+                            drop(permit);
+                            tx.send((site, Err(robots::Disallowed.into())))
+                                .await
+                                .expect("failed to send stalking result");
+                            continue;
+                        }
                     }
-                    let result = stalk_and_save_site(&from, &site).await;
-                    tx.send((site.url, result, site.options.ignore_error))
+                    let result =
+                        stalk_and_save_site(&from, &clients, &mut sessions, &site, site_state)
+                            .await;
+                    drop(permit);
+                    tx.send((site, result))
                         .await
                         .expect("failed to send stalking result");
                 }
@@ -210,24 +309,33 @@ async fn run(
     };
 
     let mut urls_of_interest = Vec::new();
+    let mut retried = Vec::new();
     let mut error_occurred = false;
     let mut amount_done: usize = 0;
-    while let Some((url, result, ignore_error)) = rx.recv().await {
+    while let Some((site, result)) = rx.recv().await {
         amount_done += 1;
         match result {
             Ok((
                 change_kind,
+                site_state,
                 http::ResponseMeta {
+                    attempts,
                     http_version,
                     ip_version,
                     took,
                     url,
+                    ..
                 },
             )) => {
+                state.set(&site, site_state.checked_at(&site, run_start));
                 eprintln!(
-                    "{amount_done:4}/{sites_amount} {change_kind:11} {:5}ms {http_version:?} {ip_version} {url}",
+                    "{amount_done:4}/{sites_amount} {change_kind:11} {:5}ms {http_version:?} {ip_version} {}",
                     took.as_millis(),
+                    secrets::redact(url.as_str()),
                 );
+                if attempts > 1 {
+                    retried.push((attempts, url.clone()));
+                }
                 match change_kind {
                     ChangeKind::Init | ChangeKind::Changed => {
                         urls_of_interest.push(url);
@@ -236,8 +344,8 @@ async fn run(
                 }
             }
             Err(err) => {
-                let message = format!("{url} {err:#}");
-                if ignore_error {
+                let message = format!("{} {err:#}", site.url);
+                if site.options.ignore_error.unwrap_or(false) || err.is::<robots::Disallowed>() {
                     logger::warn(&message);
                 } else {
                     logger::error(&message);
@@ -247,6 +355,17 @@ async fn run(
         }
     }
 
+    if !retried.is_empty() {
+        retried.sort_unstable();
+        let mut message = format!("{} sites needed more than one attempt:", retried.len());
+        for (attempts, url) in retried.iter().rev() {
+            _ = write!(message, "\n{attempts:4} attempts {url}");
+        }
+        logger::info(&message);
+    }
+
+    state.save().expect("failed to save the state file");
+
     let commit = repo
         .ok()
         .filter(git::Repo::is_something_modified)
@@ -254,7 +373,7 @@ async fn run(
             if do_commit {
                 repo.add_all();
                 let message = commit_message::commit_message(&urls_of_interest);
-                let id = repo.commit(&message);
+                let id = repo.commit(&secrets::redact(&message));
                 Some(id)
             } else {
                 logger::warn("No commit is created without the --commit flag.");
@@ -268,7 +387,7 @@ async fn run(
             notification_commit_template.or_else(github::commit_prefix),
             urls_of_interest,
         );
-        if let Err(err) = notifications.send_reqwest(&message).await {
+        if let Err(err) = notifications.send_reqwest(&secrets::redact(&message)).await {
             logger::error(&format!("notifier failed to send with Err: {err:#}"));
         }
     }
@@ -282,20 +401,38 @@ async fn run(
 
 async fn stalk_and_save_site(
     from: &HeaderValue,
+    clients: &http::Clients,
+    sessions: &mut Sessions,
     site: &Site,
-) -> anyhow::Result<(ChangeKind, http::ResponseMeta)> {
+    previous: SiteState,
+) -> anyhow::Result<(ChangeKind, SiteState, http::ResponseMeta)> {
+    // Use site.url as the file basename should only change when the config changes (manually)
+    let mut path = site.to_file_path();
+
+    let retry = site.options.retry.unwrap_or(Retry::NONE);
+    let client_options = site.client_options();
+    let client = match sessions
+        .get(&site.options.session, from, client_options, retry)
+        .await?
+    {
+        Some(client) => client,
+        None => clients.get(client_options)?,
+    };
+
     let mut headers = site.options.headers.clone();
     if !headers.contains_key(FROM) {
         headers.insert(FROM, from.clone());
     }
-    let (content, response) = http::get(
+    headers.extend(previous.conditional_headers(site, &path));
+    let (content, response) = http::request(
+        &client,
+        site.options.method,
         site.url.as_str(),
+        site.options.body.as_ref(),
         headers,
-        site.options.accept_invalid_certs,
-        site.options.http1_only,
+        retry,
     )
-    .await
-    .map_err(reqwest::Error::without_url)?;
+    .await?;
 
     if site.url.as_str() != response.url.as_str() {
         logger::warn(&format!(
@@ -304,13 +441,25 @@ async fn stalk_and_save_site(
         ));
     }
 
+    let site_state = SiteState::from_response(site, &response.headers);
+    let Some(content) = content else {
+        let site_state = site_state.or(previous);
+        return Ok((ChangeKind::ContentSame, site_state, response));
+    };
+
     // Use response.url as canonical urls for example are relative to the actual url
-    let content = editor::Editor::apply_many(&site.options.editors, &response.url, content)?;
-    let extension = content.extension.unwrap_or("txt");
+    // Editors like exec and script can take a while so keep them away from the async workers
+    let editors = site.options.editors.clone();
+    let url = response.url.clone();
+    let content =
+        tokio::task::spawn_blocking(move || editor::Editor::apply_many(&editors, &url, content))
+            .await??;
+    let extension = content.extension().unwrap_or(match content {
+        editor::Content::Text { .. } => "txt",
+        editor::Content::Binary { .. } => "bin",
+    });
 
-    // Use site.url as the file basename should only change when the config changes (manually)
-    let mut path = site.to_file_path();
     path.set_extension(extension);
-    let changed = site_store::write_only_changed(&path, &content.text)?;
-    Ok((changed, response))
+    let changed = site_store::write_only_changed(&path, &content)?;
+    Ok((changed, site_state, response))
 }
@@ -0,0 +1,114 @@
+use core::time::Duration;
+
+use schemars::JsonSchema;
+use serde::Deserialize;
+
+/// # Retry
+/// Retry failed requests with an exponential backoff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Retry {
+    /// Total amount of attempts including the first one
+    #[serde(default = "default_attempts")]
+    #[schemars(range(min = 1))]
+    pub attempts: u8,
+
+    /// Delay before the first retry. It doubles with every further retry.
+    #[serde(default = "default_backoff", with = "humantime_serde")]
+    #[schemars(with = "String")]
+    pub backoff: Duration,
+
+    /// Maximum delay before a retry.
+    /// When the server requests a longer delay via `Retry-After` there is no retry.
+    #[serde(default = "default_max_delay", with = "humantime_serde")]
+    #[schemars(with = "String")]
+    pub max_delay: Duration,
+}
+
+const fn default_attempts() -> u8 {
+    3
+}
+
+const fn default_backoff() -> Duration {
+    Duration::from_secs(1)
+}
+
+const fn default_max_delay() -> Duration {
+    Duration::from_mins(1)
+}
+
+impl Retry {
+    /// Used when nothing is configured
+    pub const NONE: Self = Self {
+        attempts: 1,
+        backoff: default_backoff(),
+        max_delay: default_max_delay(),
+    };
+
+    /// Delay before the given attempt (starting at 1) or `None` when there should be no further attempt.
+    pub fn delay(&self, attempt: u8, retry_after: Option<Duration>) -> Option<Duration> {
+        if attempt >= self.attempts {
+            return None;
+        }
+        let factor = 2_u32.saturating_pow(u32::from(attempt.saturating_sub(1)));
+        let backoff = self.backoff.saturating_mul(factor).min(self.max_delay);
+        match retry_after {
+            Some(retry_after) if retry_after > self.max_delay => None,
+            Some(retry_after) => Some(retry_after.max(backoff)),
+            None => Some(backoff),
+        }
+    }
+}
+
+#[test]
+fn none_does_not_retry() {
+    assert_eq!(Retry::NONE.delay(1, None), None);
+}
+
+#[test]
+fn backoff_doubles() {
+    let retry = Retry {
+        attempts: 4,
+        backoff: Duration::from_secs(2),
+        max_delay: Duration::from_mins(1),
+    };
+    assert_eq!(retry.delay(1, None), Some(Duration::from_secs(2)));
+    assert_eq!(retry.delay(2, None), Some(Duration::from_secs(4)));
+    assert_eq!(retry.delay(3, None), Some(Duration::from_secs(8)));
+    assert_eq!(retry.delay(4, None), None);
+}
+
+#[test]
+fn backoff_is_limited_by_max_delay() {
+    let retry = Retry {
+        attempts: 10,
+        backoff: Duration::from_secs(10),
+        max_delay: Duration::from_secs(30),
+    };
+    assert_eq!(retry.delay(5, None), Some(Duration::from_secs(30)));
+}
+
+#[test]
+fn retry_after_is_respected() {
+    let retry = Retry {
+        attempts: 3,
+        backoff: Duration::from_secs(1),
+        max_delay: Duration::from_mins(1),
+    };
+    assert_eq!(
+        retry.delay(1, Some(Duration::from_secs(20))),
+        Some(Duration::from_secs(20))
+    );
+    assert_eq!(retry.delay(1, Some(Duration::from_mins(2))), None);
+}
+
+#[test]
+fn deserialize_defaults() {
+    let retry = serde_yaml::from_str::<Retry>("attempts: 5").unwrap();
+    assert_eq!(retry.attempts, 5);
+    assert_eq!(retry.backoff, Duration::from_secs(1));
+    let retry = serde_yaml::from_str::<Retry>("backoff: 500ms\nmax_delay: 2m").unwrap();
+    assert_eq!(retry.attempts, 3);
+    assert_eq!(retry.backoff, Duration::from_millis(500));
+    assert_eq!(retry.max_delay, Duration::from_mins(2));
+}
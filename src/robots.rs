@@ -0,0 +1,148 @@
+//! Respect the [robots.txt](https://developer.mozilla.org/en-US/docs/Glossary/Robots.txt) of hosts
+
+use core::time::Duration;
+use std::collections::HashMap;
+
+use reqwest::header::{FROM, HeaderMap, HeaderValue};
+use texting_robots::Robot;
+use url::Url;
+
+use crate::http::{self, ClientOptions, Clients, Method};
+use crate::logger;
+use crate::retry::Retry;
+
+/// Product token of the [`http::USER_AGENT`] which is used to match the `User-agent` lines
+const AGENT: &str = env!("CARGO_PKG_NAME");
+
+/// The site is not stalked as the robots.txt disallows it
+#[derive(Debug)]
+pub struct Disallowed;
+
+impl core::fmt::Display for Disallowed {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        fmt.write_str(
+            "disallowed by robots.txt. Set ignore_robots_txt for this site to stalk it anyway.",
+        )
+    }
+}
+
+impl core::error::Error for Disallowed {}
+
+/// Rules of a robots.txt following [RFC 9309](https://www.rfc-editor.org/rfc/rfc9309.html#section-2.3.1)
+enum Rules {
+    /// Unavailable (4xx) or unparsable robots.txt
+    AllowAll,
+    /// Unreachable robots.txt like on server or network errors
+    DisallowAll,
+    Robot(Robot),
+}
+
+/// Robots.txt files of the origins fetched within this run
+pub struct RobotsTxt {
+    clients: Clients,
+    fetched: HashMap<String, Rules>,
+}
+
+impl RobotsTxt {
+    pub fn new(clients: Clients) -> Self {
+        Self {
+            clients,
+            fetched: HashMap::new(),
+        }
+    }
+
+    async fn get(&mut self, url: &Url, from: &HeaderValue, options: ClientOptions) -> &Rules {
+        let origin = url.origin().ascii_serialization();
+        if !self.fetched.contains_key(&origin) {
+            let rules = fetch(&self.clients, &origin, from, options).await;
+            self.fetched.insert(origin.clone(), rules);
+        }
+        &self.fetched[&origin]
+    }
+
+    /// The client options of the site are used as the host might for example require `http1_only`
+    pub async fn is_allowed(
+        &mut self,
+        url: &Url,
+        from: &HeaderValue,
+        options: ClientOptions,
+    ) -> bool {
+        match self.get(url, from, options).await {
+            Rules::AllowAll => true,
+            Rules::DisallowAll => false,
+            Rules::Robot(robot) => robot.allowed(url.as_str()),
+        }
+    }
+
+    /// The `Crawl-delay` of the robots.txt of the given URL
+    pub async fn crawl_delay(
+        &mut self,
+        url: &Url,
+        from: &HeaderValue,
+        options: ClientOptions,
+    ) -> Option<Duration> {
+        let Rules::Robot(robot) = self.get(url, from, options).await else {
+            return None;
+        };
+        Duration::try_from_secs_f32(robot.delay?).ok()
+    }
+}
+
+async fn fetch(
+    clients: &Clients,
+    origin: &str,
+    from: &HeaderValue,
+    options: ClientOptions,
+) -> Rules {
+    let url = format!("{origin}/robots.txt");
+    let mut headers = HeaderMap::new();
+    headers.insert(FROM, from.clone());
+    let result = match clients.get(options) {
+        Ok(client) => http::request(&client, Method::Get, &url, None, headers, Retry::NONE).await,
+        Err(err) => Err(err.into()),
+    };
+    let content = match result {
+        Ok((Some(content), _)) => content,
+        Ok((None, _)) => return Rules::AllowAll,
+        Err(err) => {
+            let is_client_error = err
+                .downcast_ref::<reqwest::Error>()
+                .and_then(reqwest::Error::status)
+                .is_some_and(|status| status.is_client_error());
+            // A missing robots.txt allows everything
+            if is_client_error {
+                return Rules::AllowAll;
+            }
+            logger::warn(&format!(
+                "{url} could not be loaded. Sites of this host are skipped as if it disallows everything. {err:#}"
+            ));
+            return Rules::DisallowAll;
+        }
+    };
+    match Robot::new(AGENT, content.as_bytes()) {
+        Ok(robot) => Rules::Robot(robot),
+        Err(err) => {
+            logger::warn(&format!(
+                "{url} could not be parsed. Sites of this host are stalked without respecting it. {err:#}"
+            ));
+            Rules::AllowAll
+        }
+    }
+}
+
+#[test]
+fn agent_matches_user_agent() {
+    assert!(http::USER_AGENT.starts_with(AGENT));
+}
+
+#[test]
+fn own_agent_rules_are_used() {
+    let robot = Robot::new(
+        AGENT,
+        b"User-agent: *\nDisallow: /\n\nUser-agent: website-stalker\nDisallow: /private\nCrawl-delay: 10\n",
+    )
+    .unwrap();
+    assert!(robot.allowed("https://edjopato.de/post/"));
+    assert!(!robot.allowed("https://edjopato.de/private/stuff"));
+    assert_eq!(robot.delay, Some(10.0));
+}
@@ -0,0 +1,224 @@
+//! Interpolation of `${ENV_VAR}` and `${file:/run/secrets/x}` in strings of the config.
+//!
+//! Interpolated values long enough to be a secret are redacted from logs, errors and `debug_files`.
+
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+use anyhow::Context as _;
+
+pub const REDACTED: &str = "REDACTED";
+
+/// Shorter values like `de` of `${LANG}` are likely not secret.
+/// Redacting them would also redact unrelated text like hostnames.
+const MIN_SECRET_LENGTH: usize = 8;
+
+/// Interpolated values which should not show up in any output
+static SECRETS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Replace the references in all strings of the value. Keys of mappings are kept as they are.
+/// The interpolated values are remembered to be redacted.
+pub fn interpolate_value(value: &mut serde_yaml::Value) -> anyhow::Result<()> {
+    let mut found = Vec::new();
+    let result = strings_mut(value, &mut |text| interpolate(text, &mut found));
+    register(found);
+    result
+}
+
+/// Replace the references in all strings of the value with their name like `${TOKEN}` with `TOKEN`.
+/// The result stays the same when the referenced values change and does not contain them.
+pub fn placeholder_value(value: &mut serde_yaml::Value) -> anyhow::Result<()> {
+    strings_mut(value, &mut |text| {
+        replace_references(text, |reference| {
+            Ok(reference
+                .split(|char: char| !char.is_ascii_alphanumeric())
+                .filter(|part| !part.is_empty())
+                .collect::<Vec<_>>()
+                .join("-"))
+        })
+    })
+}
+
+fn strings_mut(
+    value: &mut serde_yaml::Value,
+    replace: &mut impl FnMut(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<()> {
+    match value {
+        serde_yaml::Value::String(text) => *text = replace(text)?,
+        serde_yaml::Value::Sequence(sequence) => {
+            for value in sequence {
+                strings_mut(value, replace)?;
+            }
+        }
+        serde_yaml::Value::Mapping(mapping) => {
+            for value in mapping.values_mut() {
+                strings_mut(value, replace)?;
+            }
+        }
+        serde_yaml::Value::Tagged(tagged) => strings_mut(&mut tagged.value, replace)?,
+        serde_yaml::Value::Null | serde_yaml::Value::Bool(_) | serde_yaml::Value::Number(_) => {}
+    }
+    Ok(())
+}
+
+/// Replace `${NAME}` with the environment variable and `${file:/path}` with the file content.
+fn interpolate(text: &str, found: &mut Vec<String>) -> anyhow::Result<String> {
+    replace_references(text, |reference| {
+        let value = lookup(reference)?;
+        found.push(value.clone());
+        Ok(value)
+    })
+}
+
+/// Replace each `${reference}` with the result of `replace`. `$${` results in a literal `${`.
+fn replace_references(
+    text: &str,
+    mut replace: impl FnMut(&str) -> anyhow::Result<String>,
+) -> anyhow::Result<String> {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            result += &rest[..start];
+            result += "{";
+            rest = &rest[start + 2..];
+            continue;
+        }
+        result += &rest[..start];
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .with_context(|| format!("unterminated ${{ in {text:?}"))?;
+        result += &replace(&after[..end])?;
+        rest = &after[end + 1..];
+    }
+    result += rest;
+    Ok(result)
+}
+
+fn lookup(reference: &str) -> anyhow::Result<String> {
+    if let Some(path) = reference.strip_prefix("file:") {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("failed to read the secret file {path}"))?;
+        return Ok(content.trim_end_matches(['\n', '\r']).to_owned());
+    }
+    anyhow::ensure!(
+        !reference.is_empty()
+            && reference
+                .chars()
+                .all(|char| char.is_ascii_alphanumeric() || char == '_'),
+        "invalid reference ${{{reference}}}. Use ${{ENV_VAR}} or ${{file:/path}}"
+    );
+    std::env::var(reference)
+        .with_context(|| format!("environment variable {reference} is not available"))
+}
+
+/// Remember the values to be redacted from the output unless they are too short to be a secret
+pub fn register(found: Vec<String>) {
+    let mut secrets = SECRETS.lock().expect("secrets lock should not be poisoned");
+    secrets.extend(found.into_iter().filter(|value| is_plausible_secret(value)));
+    // Longer first so a secret containing another one is fully redacted
+    secrets.sort_unstable_by(|one, other| other.len().cmp(&one.len()).then(one.cmp(other)));
+    secrets.dedup();
+}
+
+fn is_plausible_secret(value: &str) -> bool {
+    value.chars().count() >= MIN_SECRET_LENGTH
+}
+
+/// Replace all interpolated secrets with [`REDACTED`]
+pub fn redact(text: &str) -> Cow<'_, str> {
+    let secrets = SECRETS.lock().expect("secrets lock should not be poisoned");
+    redact_with(&secrets, text)
+}
+
+fn redact_with<'t>(secrets: &[String], text: &'t str) -> Cow<'t, str> {
+    let mut result = Cow::Borrowed(text);
+    for secret in secrets {
+        if result.contains(secret.as_str()) {
+            result = Cow::Owned(result.replace(secret.as_str(), REDACTED));
+        }
+    }
+    result
+}
+
+#[test]
+fn interpolates_env() {
+    let mut found = Vec::new();
+    let result = interpolate("Bearer ${CARGO_PKG_NAME}!", &mut found).unwrap();
+    assert_eq!(result, "Bearer website-stalker!");
+    assert_eq!(found, ["website-stalker"]);
+}
+
+#[test]
+fn interpolates_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("token");
+    std::fs::write(&path, "s3cr3t\n").unwrap();
+    let mut found = Vec::new();
+    let text = format!("https://edjopato.de/?token=${{file:{}}}", path.display());
+    let result = interpolate(&text, &mut found).unwrap();
+    assert_eq!(result, "https://edjopato.de/?token=s3cr3t");
+    assert_eq!(found, ["s3cr3t"]);
+}
+
+#[test]
+fn escaped_is_kept() {
+    let mut found = Vec::new();
+    let result = interpolate("cost: $${CARGO_PKG_NAME}", &mut found).unwrap();
+    assert_eq!(result, "cost: ${CARGO_PKG_NAME}");
+    assert!(found.is_empty());
+}
+
+#[test]
+fn placeholders_are_the_names() {
+    let mut value = serde_yaml::Value::from("https://${HOST}/?token=${file:/run/secrets/x}&$${a}");
+    placeholder_value(&mut value).unwrap();
+    assert_eq!(value, "https://HOST/?token=file-run-secrets-x&${a}");
+}
+
+#[test]
+#[should_panic = "environment variable WEBSITE_STALKER_DOES_NOT_EXIST is not available"]
+fn missing_env() {
+    interpolate("${WEBSITE_STALKER_DOES_NOT_EXIST}", &mut Vec::new()).unwrap();
+}
+
+#[test]
+#[should_panic = "unterminated"]
+fn unterminated() {
+    interpolate("${CARGO_PKG_NAME", &mut Vec::new()).unwrap();
+}
+
+#[test]
+#[should_panic = "invalid reference ${foo bar}"]
+fn invalid_reference() {
+    interpolate("${foo bar}", &mut Vec::new()).unwrap();
+}
+
+#[test]
+fn redacts_longest_first() {
+    let secrets = ["abcdef".to_owned(), "abc".to_owned()];
+    assert_eq!(
+        redact_with(&secrets, "token abcdef and abc"),
+        "token REDACTED and REDACTED"
+    );
+    assert!(matches!(redact_with(&secrets, "nothing"), Cow::Borrowed(_)));
+}
+
+#[test]
+fn short_values_are_no_secrets() {
+    let mut found = Vec::new();
+    let result = interpolate("Accept-Language: ${CARGO_PKG_VERSION_MAJOR}", &mut found).unwrap();
+    assert!(result.starts_with("Accept-Language: "));
+    let secrets = found
+        .into_iter()
+        .filter(|value| is_plausible_secret(value))
+        .collect::<Vec<_>>();
+    assert!(secrets.is_empty());
+    assert!(!is_plausible_secret("de"));
+    assert!(is_plausible_secret("s3cr3t-token"));
+    assert_eq!(
+        redact_with(&secrets, "https://edjopato.de/ failed"),
+        "https://edjopato.de/ failed"
+    );
+}
@@ -0,0 +1,259 @@
+#![expect(unused_qualifications, reason = "false positive with schemars")]
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::Context as _;
+use lazy_regex::{Captures, regex};
+use reqwest::Client;
+use reqwest::cookie::Jar;
+use reqwest::header::{FROM, HeaderMap, HeaderValue};
+use schemars::JsonSchema;
+use scraper::Selector;
+use serde::Deserialize;
+use url::Url;
+
+use crate::http::{self, Body, ClientOptions, Method};
+use crate::retry::Retry;
+use crate::secrets;
+use crate::site::{deserialize_headermap, schema_headermap};
+
+/// # Session step
+/// A request done before the site itself, for example to log in.
+/// The cookies of all steps are used for the request of the site.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SessionStep {
+    pub url: Url,
+
+    #[serde(default)]
+    pub method: Method,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Body>,
+
+    #[serde(default, deserialize_with = "deserialize_headermap")]
+    #[schemars(schema_with = "schema_headermap")]
+    pub headers: HeaderMap,
+
+    /// Variables extracted from the response which can be used in the following steps as `{extract:name}`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extract: BTreeMap<String, Extract>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct Extract {
+    #[schemars(with = "String")]
+    pub selector: Selector,
+
+    /// Use the value of this attribute instead of the text content of the element
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attribute: Option<String>,
+}
+
+/// Sessions already created within this run which can be reused by other sites
+#[derive(Default)]
+pub struct Sessions {
+    created: Vec<(Vec<SessionStep>, ClientOptions, Client)>,
+}
+
+impl Sessions {
+    /// Get the client holding the cookies of the session when there is one for the site.
+    /// The session is created on first usage.
+    pub async fn get(
+        &mut self,
+        steps: &[SessionStep],
+        from: &HeaderValue,
+        options: ClientOptions,
+        retry: Retry,
+    ) -> anyhow::Result<Option<Client>> {
+        if steps.is_empty() {
+            return Ok(None);
+        }
+        if let Some((_, _, client)) = self
+            .created
+            .iter()
+            .find(|(known, known_options, _)| known == steps && *known_options == options)
+        {
+            return Ok(Some(client.clone()));
+        }
+        let client = create(steps, from, options, retry).await?;
+        self.created.push((steps.to_vec(), options, client.clone()));
+        Ok(Some(client))
+    }
+}
+
+async fn create(
+    steps: &[SessionStep],
+    from: &HeaderValue,
+    options: ClientOptions,
+    retry: Retry,
+) -> anyhow::Result<Client> {
+    let client = options.build(Some(Arc::new(Jar::default())))?;
+    let mut variables = BTreeMap::new();
+    for (i, step) in steps.iter().enumerate() {
+        step.run(&client, &mut variables, from, retry)
+            .await
+            .with_context(|| format!("in session step[{i}]"))?;
+    }
+    Ok(client)
+}
+
+impl SessionStep {
+    async fn run(
+        &self,
+        client: &Client,
+        variables: &mut BTreeMap<String, String>,
+        from: &HeaderValue,
+        retry: Retry,
+    ) -> anyhow::Result<()> {
+        let body = self
+            .body
+            .as_ref()
+            .map(|body| substitute_body(body, variables))
+            .transpose()?;
+        let mut headers = HeaderMap::new();
+        for (key, value) in &self.headers {
+            let value = value.to_str().context("header value is not ASCII")?;
+            headers.append(key, substitute(value, variables)?.parse()?);
+        }
+        if !headers.contains_key(FROM) {
+            headers.insert(FROM, from.clone());
+        }
+
+        let (content, _) = http::request(
+            client,
+            self.method,
+            self.url.as_str(),
+            body.as_ref(),
+            headers,
+            retry,
+        )
+        .await?;
+
+        if self.extract.is_empty() {
+            return Ok(());
+        }
+        let content = content.context("response has no content to extract from")?;
+        let html = scraper::Html::parse_document(content.text()?);
+        for (name, extract) in &self.extract {
+            let element = html
+                .select(&extract.selector)
+                .next()
+                .with_context(|| format!("extract {name} selected nothing"))?;
+            let value = match &extract.attribute {
+                Some(attribute) => element
+                    .value()
+                    .attr(attribute)
+                    .with_context(|| format!("extract {name} has no attribute {attribute}"))?
+                    .to_owned(),
+                None => element.text().collect::<String>().trim().to_owned(),
+            };
+            secrets::register(vec![value.clone()]);
+            variables.insert(name.clone(), value);
+        }
+        Ok(())
+    }
+}
+
+/// Replace `{extract:name}` with extracted variables.
+/// This is done for header values and the body.
+/// Other braces like in JSON or GraphQL bodies are kept as they are.
+///
+/// The errors only contain the names as the values are likely secrets.
+fn substitute(text: &str, variables: &BTreeMap<String, String>) -> anyhow::Result<String> {
+    let mut error = None;
+    let result =
+        regex!(r"\{extract:([a-zA-Z_][a-zA-Z\d_]*)\}").replace_all(text, |cap: &Captures| {
+            let name = &cap[1];
+            variables.get(name).cloned().unwrap_or_else(|| {
+                error.get_or_insert_with(|| {
+                    anyhow::anyhow!("variable {name} was not extracted before")
+                });
+                String::new()
+            })
+        });
+    if let Some(err) = error {
+        return Err(err);
+    }
+    Ok(result.into_owned())
+}
+
+fn substitute_body(body: &Body, variables: &BTreeMap<String, String>) -> anyhow::Result<Body> {
+    fn json(
+        value: &serde_json::Value,
+        variables: &BTreeMap<String, String>,
+    ) -> anyhow::Result<serde_json::Value> {
+        Ok(match value {
+            serde_json::Value::String(text) => {
+                serde_json::Value::String(substitute(text, variables)?)
+            }
+            serde_json::Value::Array(values) => serde_json::Value::Array(
+                values
+                    .iter()
+                    .map(|value| json(value, variables))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            serde_json::Value::Object(map) => serde_json::Value::Object(
+                map.iter()
+                    .map(|(key, value)| Ok((key.clone(), json(value, variables)?)))
+                    .collect::<anyhow::Result<_>>()?,
+            ),
+            other => other.clone(),
+        })
+    }
+
+    Ok(match body {
+        Body::Raw(raw) => Body::Raw(substitute(raw, variables)?),
+        Body::Json(value) => Body::Json(json(value, variables)?),
+        Body::Form(form) => Body::Form(
+            form.iter()
+                .map(|(key, value)| Ok((key.clone(), substitute(value, variables)?)))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+    })
+}
+
+#[test]
+#[expect(
+    clippy::literal_string_with_formatting_args,
+    reason = "the session syntax looks like formatting"
+)]
+fn substitute_variables() {
+    let variables = BTreeMap::from([("csrf".to_owned(), "abc".to_owned())]);
+    let result = substitute("token={extract:csrf}&keep={\"json\"}", &variables).unwrap();
+    assert_eq!(result, "token=abc&keep={\"json\"}");
+}
+
+#[test]
+fn substitute_keeps_literal_braces() {
+    let variables = BTreeMap::from([("login".to_owned(), "abc".to_owned())]);
+    let body = Body::Raw("{viewer{login}}".to_owned());
+    let body = substitute_body(&body, &variables).unwrap();
+    assert_eq!(body.to_text(), "{viewer{login}}");
+}
+
+#[test]
+fn substitute_only_extracted() {
+    // Environment variables are interpolated on config load via ${NAME}
+    let result = substitute("{env:CARGO_PKG_NAME}", &BTreeMap::new()).unwrap();
+    assert_eq!(result, "{env:CARGO_PKG_NAME}");
+}
+
+#[test]
+#[should_panic = "variable csrf was not extracted before"]
+fn substitute_unknown_variable() {
+    substitute("{extract:csrf}", &BTreeMap::new()).unwrap();
+}
+
+#[test]
+fn substitute_form_body() {
+    let variables = BTreeMap::from([("csrf".to_owned(), "abc".to_owned())]);
+    let body = Body::Form(BTreeMap::from([
+        ("csrf".to_owned(), "{extract:csrf}".to_owned()),
+        ("user".to_owned(), "me".to_owned()),
+    ]));
+    let body = substitute_body(&body, &variables).unwrap();
+    assert_eq!(body.to_text(), "csrf=abc&user=me");
+}
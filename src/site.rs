@@ -1,7 +1,9 @@
 #![expect(unused_qualifications, reason = "false positive with schemars")]
 
+use core::time::Duration;
 use std::path::{Path, PathBuf};
 
+use regex::Regex;
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -9,6 +11,9 @@ use url::Url;
 
 use crate::editor::Editor;
 use crate::filename;
+use crate::http::{Body, ClientOptions, Method};
+use crate::retry::Retry;
+use crate::session::SessionStep;
 
 #[derive(Debug)]
 pub struct Site {
@@ -19,27 +24,111 @@ pub struct Site {
 #[derive(Debug, Clone, Deserialize, JsonSchema)]
 #[serde(deny_unknown_fields)]
 pub struct Options {
-    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
-    pub accept_invalid_certs: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_invalid_certs: Option<bool>,
 
-    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
-    pub http1_only: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http1_only: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_error: Option<bool>,
+
+    /// Stalk the site even when `respect_robots_txt` is enabled and the robots.txt disallows it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_robots_txt: Option<bool>,
 
+    /// Do not use the `defaults` and the options of the `hosts` entry for this site
     #[serde(default, skip_serializing_if = "core::ops::Not::not")]
-    pub ignore_error: bool,
+    pub ignore_defaults: bool,
+
+    /// Only check the site when this time has passed since its last successful check
+    #[serde(default, with = "humantime_serde")]
+    #[schemars(with = "Option<String>")]
+    pub interval: Option<Duration>,
+
+    /// Select the sites of a run with `--tag` and `--exclude-tag`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
 
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub filename: Option<PathBuf>,
 
+    /// Overrides the global retry setting
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry: Option<Retry>,
+
+    #[serde(default)]
+    pub method: Method,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub body: Option<Body>,
+
     #[serde(default, deserialize_with = "deserialize_headermap")]
     #[schemars(schema_with = "schema_headermap")]
     pub headers: HeaderMap,
 
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub session: Vec<SessionStep>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub editors: Vec<Editor>,
 }
 
+/// Options used by sites which do not specify them on their own
+#[derive(Debug, Clone, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SiteDefaults {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_invalid_certs: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http1_only: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_error: Option<bool>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ignore_robots_txt: Option<bool>,
+
+    /// Added to the headers of the site unless the site has a header with the same name
+    #[serde(default, deserialize_with = "deserialize_headermap")]
+    #[schemars(schema_with = "schema_headermap")]
+    pub headers: HeaderMap,
+
+    /// Used when the site has no editors on its own
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub editors: Vec<Editor>,
+}
+
+impl SiteDefaults {
+    /// Fill in what the options do not specify.
+    /// Applying more specific defaults first gives them precedence.
+    pub fn apply_to(&self, options: &mut Options) {
+        options.accept_invalid_certs = options.accept_invalid_certs.or(self.accept_invalid_certs);
+        options.http1_only = options.http1_only.or(self.http1_only);
+        options.ignore_error = options.ignore_error.or(self.ignore_error);
+        options.ignore_robots_txt = options.ignore_robots_txt.or(self.ignore_robots_txt);
+        for name in self.headers.keys() {
+            if !options.headers.contains_key(name) {
+                for value in self.headers.get_all(name) {
+                    options.headers.append(name, value.clone());
+                }
+            }
+        }
+        if options.editors.is_empty() {
+            options.editors.clone_from(&self.editors);
+        }
+    }
+}
+
 impl Site {
+    pub const fn client_options(&self) -> ClientOptions {
+        ClientOptions {
+            accept_invalid_certs: matches!(self.options.accept_invalid_certs, Some(true)),
+            http1_only: matches!(self.options.http1_only, Some(true)),
+        }
+    }
+
     pub fn to_file_path(&self) -> PathBuf {
         self.options.filename.clone().unwrap_or_else(|| {
             let folder = filename::domainfolder(&self.url);
@@ -53,7 +142,17 @@ impl Site {
             for folder in rest {
                 path = path.join(folder);
             }
-            path.join(filename::filename(&self.url))
+            let mut name = filename::filename(&self.url);
+            // Requests to the same URL can differ in their method and body
+            if !self.options.method.is_get() {
+                name += "-";
+                name += &self.options.method.as_str().to_lowercase();
+            }
+            if let Some(body) = &self.options.body {
+                name += "-";
+                name += &filename::hash(&body.to_text());
+            }
+            path.join(name)
         })
     }
 
@@ -75,7 +174,28 @@ impl Site {
     }
 }
 
-fn deserialize_headermap<'de, D>(deserializer: D) -> Result<HeaderMap, D::Error>
+/// Sites to be run
+#[derive(Debug, Default)]
+pub struct Selection {
+    /// Case insensitive filter on the URL
+    pub filter: Option<Regex>,
+    /// Sites need one of these tags when not empty
+    pub tags: Vec<String>,
+    pub exclude_tags: Vec<String>,
+}
+
+impl Selection {
+    pub fn is_selected(&self, site: &Site) -> bool {
+        let has_tag = |tag: &String| site.options.tags.contains(tag);
+        self.filter
+            .as_ref()
+            .is_none_or(|filter| filter.is_match(site.url.as_str()))
+            && (self.tags.is_empty() || self.tags.iter().any(has_tag))
+            && !self.exclude_tags.iter().any(has_tag)
+    }
+}
+
+pub fn deserialize_headermap<'de, D>(deserializer: D) -> Result<HeaderMap, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -96,7 +216,7 @@ where
     Ok(result)
 }
 
-fn schema_headermap(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+pub fn schema_headermap(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
     let mut schema = Vec::<String>::json_schema(generator);
     schema.insert("minItems".to_owned(), serde_json::Value::from(1));
     schema
@@ -109,36 +229,170 @@ fn validate_finds_duplicates() {
         Site {
             url: Url::parse("https://edjopato.de/post/").unwrap(),
             options: Options {
-                accept_invalid_certs: false,
-                http1_only: false,
-                ignore_error: false,
+                accept_invalid_certs: None,
+                http1_only: None,
+                ignore_error: None,
+                ignore_robots_txt: None,
+                ignore_defaults: false,
+                tags: vec![],
+                interval: None,
                 filename: None,
+                retry: None,
+                method: Method::Get,
+                body: None,
                 headers: HeaderMap::new(),
+                session: vec![],
                 editors: vec![],
             },
         },
         Site {
             url: Url::parse("https://edjopato.de/robots.txt").unwrap(),
             options: Options {
-                accept_invalid_certs: false,
-                http1_only: false,
-                ignore_error: false,
+                accept_invalid_certs: None,
+                http1_only: None,
+                ignore_error: None,
+                ignore_robots_txt: None,
+                ignore_defaults: false,
+                tags: vec![],
+                interval: None,
                 filename: None,
+                retry: None,
+                method: Method::Get,
+                body: None,
                 headers: HeaderMap::new(),
+                session: vec![],
                 editors: vec![],
             },
         },
         Site {
             url: Url::parse("https://edjopato.de/post").unwrap(),
             options: Options {
-                accept_invalid_certs: false,
-                http1_only: false,
-                ignore_error: false,
+                accept_invalid_certs: None,
+                http1_only: None,
+                ignore_error: None,
+                ignore_robots_txt: None,
+                ignore_defaults: false,
+                tags: vec![],
+                interval: None,
                 filename: None,
+                retry: None,
+                method: Method::Get,
+                body: None,
                 headers: HeaderMap::new(),
+                session: vec![],
                 editors: vec![],
             },
         },
     ];
     Site::validate_no_duplicate(&sites).unwrap();
 }
+
+#[cfg(test)]
+fn example_site(url: &str, method: Method, body: Option<&str>) -> Site {
+    Site {
+        url: Url::parse(url).unwrap(),
+        options: Options {
+            accept_invalid_certs: None,
+            http1_only: None,
+            ignore_error: None,
+            ignore_robots_txt: None,
+            ignore_defaults: false,
+            tags: vec![],
+            interval: None,
+            filename: None,
+            retry: None,
+            method,
+            body: body.map(|body| Body::Raw(body.to_owned())),
+            headers: HeaderMap::new(),
+            session: vec![],
+            editors: vec![],
+        },
+    }
+}
+
+#[test]
+fn get_without_body_keeps_url_filename() {
+    let site = example_site("https://edjopato.de/post/", Method::Get, None);
+    assert_eq!(site.to_file_path(), Path::new("de/edjopato/post"));
+}
+
+#[test]
+fn method_is_part_of_the_filename() {
+    let site = example_site("https://edjopato.de/post/", Method::Post, None);
+    assert_eq!(site.to_file_path(), Path::new("de/edjopato/post-post"));
+}
+
+#[test]
+fn validate_same_url_different_body_is_fine() {
+    let sites = vec![
+        example_site("https://edjopato.de/search", Method::Post, Some("q=foo")),
+        example_site("https://edjopato.de/search", Method::Post, Some("q=bar")),
+        example_site("https://edjopato.de/search", Method::Post, None),
+        example_site("https://edjopato.de/search", Method::Get, None),
+    ];
+    Site::validate_no_duplicate(&sites).unwrap();
+}
+
+#[test]
+#[should_panic = "duplicates"]
+fn validate_finds_same_body_duplicates() {
+    let sites = vec![
+        example_site("https://edjopato.de/search", Method::Post, Some("q=foo")),
+        example_site("https://edjopato.de/search", Method::Post, Some("q=foo")),
+    ];
+    Site::validate_no_duplicate(&sites).unwrap();
+}
+
+#[cfg(test)]
+fn tagged(url: &str, tags: &[&str]) -> Site {
+    let config = format!("url: {url}\ntags: {tags:?}\n");
+    let entry = serde_yaml::from_str::<crate::config::SiteEntry>(&config).unwrap();
+    let crate::config::UrlVariants::Single(url) = entry.url else {
+        unreachable!();
+    };
+    Site {
+        url,
+        options: entry.options,
+    }
+}
+
+#[test]
+fn selection_by_tags() {
+    let hourly = tagged("https://edjopato.de/", &["hourly"]);
+    let legal = tagged("https://edjopato.de/impressum", &["legal", "daily"]);
+    let untagged = tagged("https://edjopato.de/post/", &[]);
+    let selected = |selection: &Selection| {
+        [&hourly, &legal, &untagged]
+            .into_iter()
+            .filter(|site| selection.is_selected(site))
+            .map(|site| site.url.path())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        selected(&Selection::default()),
+        ["/", "/impressum", "/post/"]
+    );
+    assert_eq!(
+        selected(&Selection {
+            tags: vec!["hourly".to_owned(), "legal".to_owned()],
+            ..Selection::default()
+        }),
+        ["/", "/impressum"]
+    );
+    assert_eq!(
+        selected(&Selection {
+            exclude_tags: vec!["daily".to_owned()],
+            ..Selection::default()
+        }),
+        ["/", "/post/"]
+    );
+    assert_eq!(
+        selected(&Selection {
+            filter: Some(Regex::new("(?i)IMPRESSUM").unwrap()),
+            tags: vec!["legal".to_owned()],
+            exclude_tags: vec![],
+        }),
+        ["/impressum"]
+    );
+}
@@ -1,47 +1,81 @@
-use std::fs::{create_dir_all, read_dir, read_to_string, remove_file, write};
+use std::fs::{create_dir_all, read, read_dir, remove_file, write};
 use std::path::{Path, PathBuf};
 
 use crate::ChangeKind;
+use crate::editor::Content;
 
-/// Remove site files which are no longer configured to cleanup the directory
-pub fn remove_gone(expected_paths: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
-    fn inner(expected_paths: &[PathBuf], path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+/// Remove site files which are no longer configured to cleanup the directory.
+/// The `keep` paths like the config file and its include directory are never removed.
+pub fn remove_gone(expected_paths: &[PathBuf], keep: &[PathBuf]) -> anyhow::Result<Vec<PathBuf>> {
+    remove_gone_in(Path::new("."), expected_paths, keep)
+}
+
+fn remove_gone_in(
+    root: &Path,
+    expected_paths: &[PathBuf],
+    keep: &[PathBuf],
+) -> anyhow::Result<Vec<PathBuf>> {
+    fn inner(
+        root: &Path,
+        path: &Path,
+        expected_paths: &[PathBuf],
+        keep: &[PathBuf],
+    ) -> anyhow::Result<Vec<PathBuf>> {
         let mut superfluous = Vec::new();
-        for entry in read_dir(path)? {
-            let entry = entry?.path();
-            if entry.is_dir() {
-                superfluous.append(&mut inner(expected_paths, &entry)?);
+        for entry in read_dir(root.join(path))? {
+            let entry = entry?;
+            let relative = path.join(entry.file_name());
+            let absolute = entry.path();
+            if is_kept(&absolute, keep) {
+                continue;
+            }
+            if absolute.is_dir() {
+                superfluous.append(&mut inner(root, &relative, expected_paths, keep)?);
             } else {
                 // Expected is without extension
-                let is_wanted = expected_paths.contains(&entry.with_extension(""));
+                let is_wanted = expected_paths.contains(&relative.with_extension(""));
                 if !is_wanted {
-                    remove_file(&entry)?;
-                    superfluous.push(entry);
+                    remove_file(&absolute)?;
+                    superfluous.push(relative);
                 }
             }
         }
         Ok(superfluous)
     }
 
+    let keep = keep
+        .iter()
+        .filter_map(|path| path.canonicalize().ok())
+        .collect::<Vec<_>>();
     let mut superfluous = Vec::new();
-    for entry in read_dir(".")? {
-        let entry = entry?.path();
-        if !entry.is_dir() {
+    for entry in read_dir(root)? {
+        let entry = entry?;
+        if !entry.path().is_dir() {
             continue;
         }
-        if let Some(filename) = entry.file_name() {
-            let is_relevant = filename
-                .to_str()
-                .is_some_and(|filename| !filename.starts_with('.'));
-            if is_relevant {
-                superfluous.append(&mut inner(expected_paths, Path::new(filename))?);
-            }
+        let filename = entry.file_name();
+        let is_relevant = filename
+            .to_str()
+            .is_some_and(|filename| !filename.starts_with('.'))
+            && !is_kept(&entry.path(), &keep);
+        if is_relevant {
+            superfluous.append(&mut inner(
+                root,
+                Path::new(&filename),
+                expected_paths,
+                &keep,
+            )?);
         }
     }
     superfluous.sort();
     Ok(superfluous)
 }
 
+fn is_kept(path: &Path, keep: &[PathBuf]) -> bool {
+    path.canonicalize()
+        .is_ok_and(|absolute| keep.contains(&absolute))
+}
+
 /// Remove files with the same base but a different extension.
 /// This cleans up changes of the extension like `html` -> `md`.
 fn remove_same_base_different_extension(path: &Path) -> anyhow::Result<bool> {
@@ -68,13 +102,32 @@ fn remove_same_base_different_extension(path: &Path) -> anyhow::Result<bool> {
     Ok(removed_something)
 }
 
-pub fn write_only_changed(path: &Path, content: &str) -> anyhow::Result<ChangeKind> {
+/// Check if the site was already stored with whatever extension.
+/// The `path` is expected without an extension.
+pub fn exists_with_any_extension(path: &Path) -> bool {
+    let parent = path.parent().unwrap_or_else(|| Path::new("."));
+    let Some(stem) = path.file_name() else {
+        return false;
+    };
+    read_dir(parent).is_ok_and(|entries| {
+        entries
+            .filter_map(Result::ok)
+            .any(|entry| entry.path().file_stem() == Some(stem))
+    })
+}
+
+/// Text is stored trimmed with a trailing newline while binary content is stored as is.
+/// Changes are detected by comparing the bytes.
+pub fn write_only_changed(path: &Path, content: &Content) -> anyhow::Result<ChangeKind> {
     if let Some(parent) = path.parent() {
         create_dir_all(parent)?;
     }
-    let content = content.trim().to_owned() + "\n";
+    let content = match content {
+        Content::Text { text, .. } => (text.trim().to_owned() + "\n").into_bytes(),
+        Content::Binary { bytes, .. } => bytes.clone(),
+    };
 
-    let current = read_to_string(path).unwrap_or_default();
+    let current = read(path).unwrap_or_default();
     let changed = current != content;
     if changed {
         write(path, content)?;
@@ -92,3 +145,27 @@ pub fn write_only_changed(path: &Path, content: &str) -> anyhow::Result<ChangeKi
         Ok(ChangeKind::ContentSame)
     }
 }
+
+#[test]
+fn remove_gone_keeps_include_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let root = dir.path();
+    for file in [
+        "website-stalker.d/team.yaml",
+        "de/edjopato/index.html",
+        "de/edjopato/old.html",
+    ] {
+        let path = root.join(file);
+        create_dir_all(path.parent().unwrap()).unwrap();
+        write(path, "content").unwrap();
+    }
+    let removed = remove_gone_in(
+        root,
+        &[PathBuf::from("de/edjopato/index")],
+        &[root.join("website-stalker.d")],
+    )
+    .unwrap();
+    assert_eq!(removed, [PathBuf::from("de/edjopato/old.html")]);
+    assert!(root.join("website-stalker.d/team.yaml").is_file());
+    assert!(root.join("de/edjopato/index.html").is_file());
+}
@@ -0,0 +1,305 @@
+//! Persisted information about the sites between runs.
+//!
+//! It's stored in the working directory and should be committed alongside the site files.
+
+use core::fmt::Write as _;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::{
+    ETAG, HeaderMap, HeaderName, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::filename;
+use crate::site::Site;
+
+const FILENAME: &str = ".website-stalker-state.json";
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct State {
+    /// Keyed by [`Site::to_file_path`]
+    #[serde(default)]
+    sites: BTreeMap<String, SiteState>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SiteState {
+    /// Hash of the site options the validators were created with.
+    /// When the options change the content has to be fetched again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    options: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+
+    /// Start of the run which last checked the site successfully.
+    /// Only kept for sites with an `interval` to not change the file on every run.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "humantime_serde"
+    )]
+    last_check: Option<SystemTime>,
+}
+
+impl State {
+    pub fn load() -> anyhow::Result<Self> {
+        match fs::read_to_string(FILENAME) {
+            Ok(content) => Ok(serde_json::from_str(&content)?),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if self.sites.is_empty() {
+            if Path::new(FILENAME).exists() {
+                fs::remove_file(FILENAME)?;
+            }
+            return Ok(());
+        }
+        let content = serde_json::to_string_pretty(self)? + "\n";
+        fs::write(FILENAME, content)?;
+        Ok(())
+    }
+
+    pub fn get(&self, site: &Site) -> SiteState {
+        self.sites.get(&key(site)).cloned().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, site: &Site, state: SiteState) {
+        let key = key(site);
+        if state == SiteState::default() {
+            self.sites.remove(&key);
+        } else {
+            self.sites.insert(key, state);
+        }
+    }
+
+    /// Remove the state of sites which are no longer configured
+    pub fn retain(&mut self, sites: &[Site]) {
+        let keys = sites.iter().map(key).collect::<Vec<_>>();
+        self.sites.retain(|key, _| keys.contains(key));
+    }
+}
+
+impl SiteState {
+    /// Create the state from the response headers of the site
+    pub fn from_response(site: &Site, headers: &HeaderMap) -> Self {
+        let header = |name: HeaderName| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(ToOwned::to_owned)
+        };
+        let etag = header(ETAG);
+        let last_modified = header(LAST_MODIFIED);
+        let options = (etag.is_some() || last_modified.is_some()).then(|| options_hash(site));
+        Self {
+            options,
+            etag,
+            last_modified,
+            last_check: None,
+        }
+    }
+
+    /// Keep the current validators when the server did not send new ones
+    pub fn or(self, previous: Self) -> Self {
+        if self.etag.is_none() && self.last_modified.is_none() {
+            previous
+        } else {
+            self
+        }
+    }
+
+    /// Remember the successful check when the site has an interval
+    pub fn checked_at(mut self, site: &Site, run_start: SystemTime) -> Self {
+        self.last_check = site.options.interval.map(|_| {
+            // Whole seconds keep the state file readable
+            let seconds = run_start
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            SystemTime::UNIX_EPOCH + Duration::from_secs(seconds)
+        });
+        self
+    }
+
+    /// Whether the interval of the site has passed since its last check.
+    ///
+    /// Sites are already due 5 % before the interval passed.
+    /// Otherwise, a timer running as often as the interval would only check the site on every second run because of small delays.
+    pub fn is_due(&self, site: &Site, now: SystemTime) -> bool {
+        let (Some(interval), Some(last_check)) = (site.options.interval, self.last_check) else {
+            return true;
+        };
+        now.duration_since(last_check).map_or(true, |elapsed| {
+            elapsed >= interval.saturating_sub(interval / 20)
+        })
+    }
+
+    /// Headers for a conditional request when the validators are still usable for the site.
+    ///
+    /// Only GET requests are made conditional as other methods would treat them as preconditions.
+    pub fn conditional_headers(&self, site: &Site, stored: &Path) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let usable = site.options.method.is_get()
+            && self.options.as_deref() == Some(options_hash(site).as_str())
+            && crate::site_store::exists_with_any_extension(stored);
+        if !usable {
+            return headers;
+        }
+        if let Some(value) = self.etag.as_deref().and_then(|etag| etag.parse().ok()) {
+            headers.insert(IF_NONE_MATCH, value);
+        }
+        if let Some(value) = self
+            .last_modified
+            .as_deref()
+            .and_then(|last_modified| last_modified.parse().ok())
+        {
+            headers.insert(IF_MODIFIED_SINCE, value);
+        }
+        headers
+    }
+}
+
+fn key(site: &Site) -> String {
+    site.to_file_path().to_string_lossy().replace('\\', "/")
+}
+
+/// Changing options like editors can change the result without the server content changing.
+///
+/// Only options without secrets are part of the hash as it ends up in the committed state file.
+/// The filename covers the URL and the body without their interpolated values.
+/// Header values and the session likely contain credentials so only the header names are included.
+///
+/// The hash is not guaranteed to be stable between versions of website-stalker.
+/// A mismatch only results in a full request.
+fn options_hash(site: &Site) -> String {
+    let mut input = format!("{}\n{}", key(site), site.options.method.as_str());
+    for name in site.options.headers.keys() {
+        _ = write!(input, "\n{name}");
+    }
+    for editor in &site.options.editors {
+        _ = write!(input, "\n{editor:?}");
+    }
+    filename::hash(&input)
+}
+
+#[cfg(test)]
+fn example_site(url: &str) -> Site {
+    Site {
+        url: url::Url::parse(url).unwrap(),
+        options: serde_yaml::from_str("{}").unwrap(),
+    }
+}
+
+#[test]
+fn from_response_without_validators_is_default() {
+    let site = example_site("https://edjopato.de/");
+    let state = SiteState::from_response(&site, &HeaderMap::new());
+    assert_eq!(state, SiteState::default());
+}
+
+#[test]
+fn from_response_takes_validators() {
+    let site = example_site("https://edjopato.de/");
+    let mut headers = HeaderMap::new();
+    headers.insert(ETAG, reqwest::header::HeaderValue::from_static("\"abc\""));
+    headers.insert(
+        LAST_MODIFIED,
+        reqwest::header::HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+    );
+    let state = SiteState::from_response(&site, &headers);
+    assert_eq!(state.etag.as_deref(), Some("\"abc\""));
+    assert_eq!(
+        state.last_modified.as_deref(),
+        Some("Wed, 21 Oct 2015 07:28:00 GMT")
+    );
+    assert!(state.options.is_some());
+}
+
+#[test]
+fn options_change_the_hash() {
+    let plain = example_site("https://edjopato.de/");
+    let mut edited = example_site("https://edjopato.de/");
+    edited.options.editors = serde_yaml::from_str("[html_prettify]").unwrap();
+    assert_ne!(options_hash(&plain), options_hash(&edited));
+}
+
+#[test]
+fn header_values_are_not_hashed() {
+    let mut one = example_site("https://edjopato.de/");
+    let mut other = example_site("https://edjopato.de/");
+    one.options.headers.insert(
+        reqwest::header::AUTHORIZATION,
+        reqwest::header::HeaderValue::from_static("Bearer one"),
+    );
+    other.options.headers.insert(
+        reqwest::header::AUTHORIZATION,
+        reqwest::header::HeaderValue::from_static("Bearer other"),
+    );
+    assert_eq!(options_hash(&one), options_hash(&other));
+    assert_ne!(
+        options_hash(&one),
+        options_hash(&example_site("https://edjopato.de/"))
+    );
+}
+
+#[test]
+fn retain_removes_unconfigured() {
+    let kept = example_site("https://edjopato.de/");
+    let gone = example_site("https://edjopato.de/post/");
+    let validators = SiteState {
+        options: None,
+        etag: Some("\"abc\"".to_owned()),
+        last_modified: None,
+        last_check: None,
+    };
+    let mut state = State::default();
+    state.set(&kept, validators.clone());
+    state.set(&gone, validators.clone());
+    state.retain(std::slice::from_ref(&kept));
+    assert_eq!(state.get(&kept), validators);
+    assert_eq!(state.get(&gone), SiteState::default());
+}
+
+#[test]
+fn due_by_interval() {
+    let mut site = example_site("https://edjopato.de/");
+    let start = SystemTime::UNIX_EPOCH + Duration::from_hours(500_000);
+    let hour = Duration::from_hours(1);
+
+    let state = SiteState::default().checked_at(&site, start);
+    assert_eq!(state, SiteState::default(), "without interval");
+    assert!(state.is_due(&site, start));
+
+    site.options.interval = Some(hour);
+    let state = SiteState::default().checked_at(&site, start);
+    assert!(!state.is_due(&site, start));
+    assert!(!state.is_due(&site, start + hour / 2));
+    assert!(state.is_due(&site, start + hour - Duration::from_mins(1)));
+    assert!(state.is_due(&site, start + hour * 2));
+    assert!(
+        state.is_due(&site, start - hour),
+        "last check in the future"
+    );
+    assert!(SiteState::default().is_due(&site, start), "never checked");
+}
+
+#[test]
+fn last_check_is_readable() {
+    let mut site = example_site("https://edjopato.de/");
+    site.options.interval = Some(Duration::from_mins(1));
+    let start = SystemTime::UNIX_EPOCH + Duration::from_millis(1_800_000_000_123);
+    let state = SiteState::default().checked_at(&site, start);
+    let json = serde_json::to_string(&state).unwrap();
+    assert_eq!(json, r#"{"last_check":"2027-01-15T08:00:00Z"}"#);
+    assert_eq!(serde_json::from_str::<SiteState>(&json).unwrap(), state);
+}